@@ -0,0 +1,917 @@
+//! The `Vec<char>` based lexer that the byte-oriented `opal::compiler::Lexer` replaced, kept
+//! verbatim as the baseline for the throughput comparison in `main.rs`.
+
+#![allow(dead_code)]
+#![allow(unused_variables)]
+#![allow(unused_mut)]
+#![allow(clippy::all)]
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub struct TextPosition {
+    pub absolute: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenPosition {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+impl TokenPosition {
+    pub fn new(start: TextPosition, end: TextPosition) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Keyword {
+    U8,
+    I8,
+    U16,
+    I16,
+    Fn,
+    SizeOf,
+    Type,
+    Const,
+    Var,
+    If,
+    Else,
+    While,
+    For,
+    Switch,
+    Case,
+    Default,
+    Break,
+    Continue,
+    Return,
+    Extern,
+    Struct,
+    Union,
+    Enum,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum IntegerLiteralKind {
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: TokenPosition,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, position: TokenPosition) -> Self {
+        Self { kind, position }
+    }
+
+    pub fn keyword(key: Keyword, position: TokenPosition) -> Self {
+        Self {
+            kind: TokenKind::Keyword(key),
+            position,
+        }
+    }
+    pub fn from_kind(kind: TokenKind) -> Self {
+        Self {
+            kind,
+            position: TokenPosition {
+                start: TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                end: TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+            },
+        }
+    }
+
+    pub fn from_kinds(kinds: &[TokenKind]) -> Vec<Self> {
+        kinds
+            .iter()
+            .map(|kind| Self::from_kind(kind.clone()))
+            .collect()
+    }
+}
+
+impl Default for Token {
+    fn default() -> Self {
+        Self {
+            kind: TokenKind::Illegal,
+            position: TokenPosition {
+                start: TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                end: TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TokenKind {
+    Keyword(Keyword),
+    Identifier(String),
+    IntegerLiteral(IntegerLiteralKind, u32, String),
+    CharLiteral(char),
+    StringLiteral(String),
+    LeftBracket,
+    RightBracket,
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBrace,
+    RightBrace,
+    LeftChevron,
+    RightChevron,
+    Colon,
+    SemiColon,
+    Comma,
+    Plus,
+    Minus,
+    Bang,
+    Ampersand,
+    DoubleAmpersand,
+    Bar,
+    DoubleBar,
+    Caret,
+    Equal,
+    DoubleEqual,
+    BangEqual,
+    LessThan,
+    LessThanEqual,
+    GreaterThan,
+    GreaterThanEqual,
+    PlusEqual,
+    MinusEqual,
+    AsteriskEqual,
+    ForwardSlashEqual,
+    PercentEqual,
+    LeftChevronEqual,
+    RightChevronEqual,
+    AmpersandEqual,
+    BarEqual,
+    CaretEqual,
+    Asterisk,
+    ForwardSlash,
+    Percent,
+    Dot,
+    Whitespace(usize),
+    Comment(String),
+    EOF,
+    Illegal,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedEOF,
+    UnrecognizedCharacter,
+    MultipleCharLiteral,
+    EmptyCharLiteral,
+    EmptyStringLiteral,
+    EmptyIntegerLiteral,
+    IntegerLiteralOutOfRange,
+    UnknownEscapeSequence,
+    NonconformingLexeme,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    msg: String,
+    position: TextPosition,
+    end: TextPosition,
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, position: TextPosition) -> Self {
+        LexError {
+            kind,
+            msg: String::from("Not given"),
+            position,
+            end: position,
+        }
+    }
+
+    pub fn with_msg(self, msg: String) -> Self {
+        return Self { msg, ..self };
+    }
+
+    /// Extends the error to cover the whole offending lexeme rather than just its first character.
+    pub fn with_end(self, end: TextPosition) -> Self {
+        return Self { end, ..self };
+    }
+
+    pub fn get_position(&self) -> TextPosition {
+        return self.position;
+    }
+
+    pub fn get_span(&self) -> TokenPosition {
+        return TokenPosition::new(self.position, self.end);
+    }
+}
+
+/// How large an integer literal the lexer accepts before reporting `IntegerLiteralOutOfRange`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegerRange {
+    /// Literals must fit in a `u16`, the widest BW8 integer type.
+    Native,
+    /// Literals may use the full `u32` range. The parser is then responsible for range checking
+    /// against the target type, which lets it fold a leading `-` into the value first (`-32768`).
+    Wide,
+}
+
+impl IntegerRange {
+    fn max(self) -> u32 {
+        match self {
+            IntegerRange::Native => u16::MAX as u32,
+            IntegerRange::Wide => u32::MAX,
+        }
+    }
+}
+
+pub type LexResult<T> = Result<T, LexError>;
+
+pub struct Lexer {
+    input: Vec<char>,
+    position: TextPosition,
+    integer_range: IntegerRange,
+    errors: Vec<LexError>,
+    finished: bool,
+}
+
+impl Lexer {
+    pub fn new(input: &str) -> Self {
+        Lexer {
+            input: String::from(input).chars().collect(),
+            position: TextPosition {
+                absolute: 0,
+                line: 0,
+                column: 0,
+            },
+            integer_range: IntegerRange::Native,
+            errors: Vec::new(),
+            finished: false,
+        }
+    }
+
+    pub fn with_integer_range(self, integer_range: IntegerRange) -> Self {
+        Self {
+            integer_range,
+            ..self
+        }
+    }
+
+    /// Lexes the next token like the `Iterator` implementation, but never produces an error. The
+    /// error is recorded instead (see `take_errors`) and a `TokenKind::Illegal` token spanning the
+    /// skipped lexeme is returned in its place.
+    pub fn next_token_recovering(&mut self) -> Token {
+        let start = self.position;
+
+        match self.next() {
+            Some(Ok(token)) => token,
+            Some(Err(err)) => {
+                self.errors.push(err);
+                Token::new(TokenKind::Illegal, TokenPosition::new(start, self.position))
+            }
+            None => Token::new(
+                TokenKind::EOF,
+                TokenPosition::new(self.position, self.position),
+            ),
+        }
+    }
+
+    /// Returns the errors recorded by `next_token_recovering` so far.
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Lexes the next token, or a `TokenKind::EOF` token once the input is exhausted. A bad lexeme
+    /// is not necessarily skipped; use the `Iterator` implementation to keep lexing past errors.
+    pub fn next_token(&mut self) -> LexResult<Token> {
+        use LexErrorKind::*;
+
+        if self.current().is_none() {
+            return Ok(Token::new(
+                TokenKind::EOF,
+                TokenPosition::new(self.position, self.position),
+            ));
+        }
+
+        match self.next_whitespace() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                UnrecognizedCharacter => return Err(err),
+                _ => (),
+            },
+        }
+
+        match self.next_comment() {
+            Ok(token) => return Ok(token),
+            Err(err) => {
+                match err.kind {
+                    // UnexpectedEOF => todo!(),
+                    // UnrecognizedCharacter => todo!(),
+                    // MultipleCharLiteral => todo!(),
+                    // EmptyCharLiteral => todo!(),
+                    // EmptyStringLiteral => todo!(),
+                    // NonconformingLexeme => todo!(),
+                    _ => (),
+                }
+            }
+        }
+
+        match self.next_integer_literal() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                EmptyIntegerLiteral => return Err(err),
+                IntegerLiteralOutOfRange => return Err(err),
+                _ => (),
+            },
+        }
+
+        match self.next_char_literal() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                UnrecognizedCharacter => return Err(err),
+                MultipleCharLiteral => return Err(err),
+                EmptyCharLiteral => return Err(err),
+                UnknownEscapeSequence => return Err(err),
+                _ => (),
+            },
+        }
+
+        match self.next_string_literal() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                UnrecognizedCharacter => return Err(err),
+                EmptyStringLiteral => return Err(err),
+                UnknownEscapeSequence => return Err(err),
+                _ => (),
+            },
+        }
+
+        match self.next_identifier_or_keyword() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                UnrecognizedCharacter => return Err(err),
+                _ => (),
+            },
+        }
+
+        match self.next_primitive_token() {
+            Ok(token) => return Ok(token),
+            Err(err) => match err.kind {
+                UnexpectedEOF => return Err(err),
+                UnrecognizedCharacter => return Err(err),
+                _ => (),
+            },
+        }
+
+        LexResult::Err(
+            LexError::new(LexErrorKind::UnrecognizedCharacter, self.position).with_msg(format!(
+                "Unrecognized character '{}'",
+                self.current().unwrap()
+            )),
+        )
+    }
+
+    fn current(&self) -> Option<char> {
+        self.input.get(self.position.absolute).copied()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position.absolute + 1).copied()
+    }
+
+    fn advance(&mut self) {
+        match self.current() {
+            None => (),
+            Some(char) => {
+                if char == '\n' {
+                    self.position.line += 1;
+                    self.position.column = 0;
+                }
+                self.position.absolute += 1;
+                self.position.column += 1;
+            }
+        }
+    }
+
+    fn advance_by(&mut self, by: usize) -> String {
+        let mut result = String::new();
+        let mut count = by;
+
+        while let Some(c) = self.current() {
+            if count > 0 {
+                self.advance();
+                result.push(c);
+            } else {
+                break;
+            }
+            count -= 1;
+        }
+
+        result
+    }
+
+    fn advance_while(&mut self, predicate: fn(char) -> bool) -> Vec<char> {
+        let mut result = Vec::new();
+
+        while let Some(c) = self.current() {
+            if predicate(c) {
+                self.advance();
+                result.push(c);
+            } else {
+                break;
+            }
+        }
+
+        result
+    }
+
+    fn next_whitespace(&mut self) -> LexResult<Token> {
+        let start = self.position;
+
+        let consumed = self.advance_while(|c| c.is_ascii_whitespace());
+
+        match consumed.len() {
+            0 => LexResult::Err(LexError::new(
+                LexErrorKind::NonconformingLexeme,
+                self.position,
+            )),
+            _ => LexResult::Ok(Token::new(
+                TokenKind::Whitespace(
+                    consumed
+                        .into_iter()
+                        .filter(|&c| c == '\n')
+                        .collect::<String>()
+                        .len(),
+                ),
+                TokenPosition::new(start, self.position),
+            )),
+        }
+    }
+
+    fn next_comment(&mut self) -> LexResult<Token> {
+        use LexErrorKind::{NonconformingLexeme, UnexpectedEOF};
+        use TokenKind::Comment;
+
+        let start = self.position;
+        let mut comment: Vec<char>;
+
+        match self.current() {
+            None => Err(LexError::new(UnexpectedEOF, self.position)),
+            Some('#') => {
+                self.advance();
+                comment = self.advance_while(|c| c != '\n');
+                Ok(Token::new(
+                    Comment(comment.iter().collect()),
+                    TokenPosition {
+                        start,
+                        end: self.position,
+                    },
+                ))
+            }
+            Some(c) => Err(LexError::new(NonconformingLexeme, self.position)
+                .with_msg(format!("Expected `#`, found {}", c))),
+        }
+    }
+
+    fn next_integer_literal(&mut self) -> LexResult<Token> {
+        use IntegerLiteralKind::{Binary, Decimal, Hexadecimal};
+
+        let start = self.position;
+
+        // "0x" and "0b" prefixes select the radix. Anything else is decimal.
+        let (kind, radix, is_digit): (IntegerLiteralKind, u32, fn(char) -> bool) =
+            match (self.current(), self.peek()) {
+                (Some('0'), Some('x')) => (Hexadecimal, 16, is_opal_hexadecimal_digit),
+                (Some('0'), Some('b')) => (Binary, 2, is_opal_binary_digit),
+                _ => (Decimal, 10, is_opal_decimal_digit),
+            };
+
+        let mut spelling = match kind {
+            Decimal => String::new(),
+            _ => self.advance_by(2),
+        };
+
+        let consumed = self.advance_while(is_digit);
+        spelling.extend(consumed.iter());
+
+        let digits: String = consumed.into_iter().filter(|&c| c != '_').collect();
+
+        match (digits.len(), &kind) {
+            (0, Decimal) => {
+                self.position = start;
+                Err(LexError::new(
+                    LexErrorKind::UnrecognizedCharacter,
+                    self.position,
+                ))
+            }
+            (0, _) => Err(
+                LexError::new(LexErrorKind::EmptyIntegerLiteral, start).with_msg(format!(
+                    "Integer literal `{}` has no digits after its prefix",
+                    spelling
+                )),
+            ),
+            _ => match u32::from_str_radix(&digits, radix) {
+                Ok(literal) if literal <= self.integer_range.max() => Ok(Token::new(
+                    TokenKind::IntegerLiteral(kind, literal, spelling),
+                    TokenPosition::new(start, self.position),
+                )),
+                _ => Err(LexError::new(LexErrorKind::IntegerLiteralOutOfRange, start)
+                    .with_end(self.position)
+                    .with_msg(format!(
+                        "Integer literal `{}` exceeds the maximum of {}",
+                        spelling,
+                        self.integer_range.max()
+                    ))),
+            },
+        }
+    }
+
+    fn next_char_literal(&mut self) -> LexResult<Token> {
+        use LexErrorKind::{
+            EmptyCharLiteral, MultipleCharLiteral, NonconformingLexeme, UnexpectedEOF,
+        };
+        use TokenKind::CharLiteral;
+
+        let start = self.position;
+        let mut literal = Vec::new();
+        let mut escape_error = None;
+
+        match self.current() {
+            None => return Err(LexError::new(UnexpectedEOF, self.position)),
+            Some('\'') => {
+                self.advance();
+
+                loop {
+                    match self.current() {
+                        None => {
+                            return Err(LexError::new(UnexpectedEOF, self.position)
+                                .with_msg(String::from("Unexpected EOF when searching for `'`")))
+                        }
+                        Some('\'') => break,
+                        // A bad escape is only reported once the closing `'` is found, so that
+                        // the whole literal is skipped rather than just the escape.
+                        Some('\\') => match self.next_escape_sequence() {
+                            Ok(c) => literal.push(c),
+                            Err(err) => {
+                                escape_error.get_or_insert(err);
+                            }
+                        },
+                        Some(c) => {
+                            self.advance();
+                            literal.push(c);
+                        }
+                    }
+                }
+            }
+            Some(c) => {
+                return Err(LexError::new(NonconformingLexeme, self.position)
+                    .with_msg(format!("Expected `'`, found {}", c)))
+            }
+        }
+
+        let end = self.position;
+        self.advance();
+
+        if let Some(err) = escape_error {
+            return Err(err);
+        }
+
+        match literal.len() {
+            1 => Ok(Token::new(
+                CharLiteral(literal[0]),
+                TokenPosition::new(start, end),
+            )),
+            0 => Err(LexError::new(EmptyCharLiteral, start)
+                .with_end(self.position)
+                .with_msg(String::from("Character literal may not be empty."))),
+            n => Err(LexError::new(MultipleCharLiteral, start)
+                .with_end(self.position)
+                .with_msg(format!(
+                    "Character literal may only contain 1 character. Found {}",
+                    n
+                ))),
+        }
+    }
+
+    fn next_string_literal(&mut self) -> LexResult<Token> {
+        use LexErrorKind::UnexpectedEOF;
+
+        let start = self.position;
+        let mut literal: Vec<char> = Vec::new();
+        let mut escape_error = None;
+
+        match self.current() {
+            None => Err(LexError::new(UnexpectedEOF, self.position)),
+            Some('"') => {
+                self.advance();
+                loop {
+                    match self.current() {
+                        None => {
+                            return Err(LexError::new(LexErrorKind::UnexpectedEOF, self.position))
+                        }
+                        Some('"') => {
+                            let rv = match escape_error {
+                                Some(err) => Err(err),
+                                None => Ok(Token::new(
+                                    TokenKind::StringLiteral(
+                                        literal.into_iter().collect::<String>(),
+                                    ),
+                                    TokenPosition::new(start, self.position),
+                                )),
+                            };
+                            self.advance();
+                            return rv;
+                        }
+                        Some('\\') => match self.next_escape_sequence() {
+                            Ok(c) => literal.push(c),
+                            Err(err) => {
+                                escape_error.get_or_insert(err);
+                            }
+                        },
+                        Some(c) => {
+                            self.advance();
+                            literal.push(c);
+                        }
+                    }
+                }
+            }
+            Some(_) => Err(LexError::new(
+                LexErrorKind::NonconformingLexeme,
+                self.position,
+            )),
+        }
+    }
+
+    /// Decodes the escape sequence starting at the current `\`, leaving the lexer just past it.
+    fn next_escape_sequence(&mut self) -> LexResult<char> {
+        use LexErrorKind::{UnexpectedEOF, UnknownEscapeSequence};
+
+        let start = self.position;
+        self.advance();
+
+        let escaped = match self.current() {
+            None => {
+                return Err(LexError::new(UnexpectedEOF, self.position)
+                    .with_msg(String::from("Unexpected EOF in escape sequence")))
+            }
+            Some(c) => c,
+        };
+        self.advance();
+
+        match escaped {
+            'b' => Ok('\u{08}'),
+            'f' => Ok('\u{0C}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            '\\' => Ok('\\'),
+            '\'' => Ok('\''),
+            '"' => Ok('"'),
+            'x' => {
+                let mut digits = String::new();
+
+                while digits.len() < 2 {
+                    match self.current() {
+                        Some(c) if c.is_ascii_hexdigit() => {
+                            self.advance();
+                            digits.push(c);
+                        }
+                        _ => break,
+                    }
+                }
+
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.len() == 2 => Ok(char::from(byte)),
+                    _ => Err(LexError::new(UnknownEscapeSequence, start)
+                        .with_end(self.position)
+                        .with_msg(format!(
+                            "Escape sequence `\\x{}` must have exactly two hexadecimal digits",
+                            digits
+                        ))),
+                }
+            }
+            c => Err(LexError::new(UnknownEscapeSequence, start)
+                .with_end(self.position)
+                .with_msg(format!("Unknown escape sequence `\\{}`", c))),
+        }
+    }
+
+    fn next_identifier_or_keyword(&mut self) -> LexResult<Token> {
+        use LexErrorKind::UnexpectedEOF;
+
+        let start = self.position;
+        let mut token = String::new();
+
+        match self.current() {
+            None => return Err(LexError::new(UnexpectedEOF, self.position)),
+            Some(c) => {
+                if !is_ident_head(c) {
+                    self.position = start;
+                    return Err(LexError::new(
+                        LexErrorKind::NonconformingLexeme,
+                        self.position,
+                    ));
+                } else {
+                    token.push(c);
+                    self.advance();
+                }
+            }
+        }
+
+        loop {
+            match self.current() {
+                None => return self.to_identifier_or_keyword(start, token.as_str()),
+                Some(c) => {
+                    if !is_ident_tail(c) {
+                        return self.to_identifier_or_keyword(start, token.as_str());
+                    }
+
+                    token.push(c);
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn to_identifier_or_keyword(&self, start: TextPosition, input: &str) -> LexResult<Token> {
+        let position = TokenPosition {
+            start,
+            end: self.position,
+        };
+
+        match input {
+            "u8" => Ok(Token::keyword(Keyword::U8, position)),
+            "i8" => Ok(Token::keyword(Keyword::I8, position)),
+            "u16" => Ok(Token::keyword(Keyword::U16, position)),
+            "i16" => Ok(Token::keyword(Keyword::I16, position)),
+            "fn" => Ok(Token::keyword(Keyword::Fn, position)),
+            "sizeof" => Ok(Token::keyword(Keyword::SizeOf, position)),
+            "type" => Ok(Token::keyword(Keyword::Type, position)),
+            "const" => Ok(Token::keyword(Keyword::Const, position)),
+            "var" => Ok(Token::keyword(Keyword::Var, position)),
+            "if" => Ok(Token::keyword(Keyword::If, position)),
+            "else" => Ok(Token::keyword(Keyword::Else, position)),
+            "while" => Ok(Token::keyword(Keyword::While, position)),
+            "for" => Ok(Token::keyword(Keyword::For, position)),
+            "switch" => Ok(Token::keyword(Keyword::Switch, position)),
+            "case" => Ok(Token::keyword(Keyword::Case, position)),
+            "default" => Ok(Token::keyword(Keyword::Default, position)),
+            "break" => Ok(Token::keyword(Keyword::Break, position)),
+            "continue" => Ok(Token::keyword(Keyword::Continue, position)),
+            "return" => Ok(Token::keyword(Keyword::Return, position)),
+            "extern" => Ok(Token::keyword(Keyword::Extern, position)),
+            "struct" => Ok(Token::keyword(Keyword::Struct, position)),
+            "union" => Ok(Token::keyword(Keyword::Union, position)),
+            "enum" => Ok(Token::keyword(Keyword::Enum, position)),
+            ident => Ok(Token::new(
+                TokenKind::Identifier(String::from(ident)),
+                position,
+            )),
+        }
+    }
+
+    fn next_primitive_token(&mut self) -> LexResult<Token> {
+        use LexErrorKind::NonconformingLexeme;
+
+        const PATTERNS: [(&str, TokenKind); 40] = [
+            ("<<=", TokenKind::LeftChevronEqual),
+            (">>=", TokenKind::RightChevronEqual),
+            ("<<", TokenKind::LeftChevron),
+            (">>", TokenKind::RightChevron),
+            ("+=", TokenKind::PlusEqual),
+            ("-=", TokenKind::MinusEqual),
+            ("*=", TokenKind::AsteriskEqual),
+            ("/=", TokenKind::ForwardSlashEqual),
+            ("%=", TokenKind::PercentEqual),
+            ("&=", TokenKind::AmpersandEqual),
+            ("|=", TokenKind::BarEqual),
+            ("^=", TokenKind::CaretEqual),
+            ("==", TokenKind::DoubleEqual),
+            ("!=", TokenKind::BangEqual),
+            ("<=", TokenKind::LessThanEqual),
+            (">=", TokenKind::GreaterThanEqual),
+            ("&&", TokenKind::DoubleAmpersand),
+            ("||", TokenKind::DoubleBar),
+            ("+", TokenKind::Plus),
+            ("-", TokenKind::Minus),
+            ("!", TokenKind::Bang),
+            ("&", TokenKind::Ampersand),
+            ("|", TokenKind::Bar),
+            ("^", TokenKind::Caret),
+            ("=", TokenKind::Equal),
+            ("<", TokenKind::LessThan),
+            (">", TokenKind::GreaterThan),
+            ("*", TokenKind::Asterisk),
+            ("/", TokenKind::ForwardSlash),
+            ("%", TokenKind::Percent),
+            (".", TokenKind::Dot),
+            ("[", TokenKind::LeftBracket),
+            ("]", TokenKind::RightBracket),
+            ("(", TokenKind::LeftParenthesis),
+            (")", TokenKind::RightParenthesis),
+            ("{", TokenKind::LeftBrace),
+            ("}", TokenKind::RightBrace),
+            (":", TokenKind::Colon),
+            (";", TokenKind::SemiColon),
+            (",", TokenKind::Comma),
+        ];
+
+        let start = self.position;
+
+        for (pattern, token) in PATTERNS.iter() {
+            if self.next_match(pattern).is_ok() {
+                return Ok(Token::new(
+                    token.clone(),
+                    TokenPosition::new(start, self.position),
+                ));
+            }
+        }
+
+        Err(LexError::new(NonconformingLexeme, self.position))
+    }
+
+    fn next_match(&mut self, pattern: &str) -> LexResult<()> {
+        use LexErrorKind::NonconformingLexeme;
+
+        let start = self.position;
+        let s = self.advance_by(pattern.chars().count());
+
+        if s.eq(pattern) {
+            Ok(())
+        } else {
+            self.position = start;
+            Err(LexError::new(NonconformingLexeme, self.position))
+        }
+    }
+}
+
+/// Lexes tokens lazily, ending with a single `TokenKind::EOF` token. Lexing continues past a bad
+/// lexeme: its error is yielded and the lexeme skipped.
+impl Iterator for Lexer {
+    type Item = LexResult<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let start = self.position;
+        let result = self.next_token();
+
+        match &result {
+            Ok(token) => self.finished = token.kind == TokenKind::EOF,
+            Err(_) => {
+                // Every bad lexeme must be skipped, even the ones that leave the lexer where
+                // they started, or else lexing would never make progress.
+                if self.position == start {
+                    self.advance();
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+fn is_ident_head(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_tail(c: char) -> bool {
+    is_ident_head(c) || c.is_ascii_digit()
+}
+
+fn is_opal_decimal_digit(c: char) -> bool {
+    c.is_ascii_digit() || c == '_'
+}
+
+fn is_opal_hexadecimal_digit(c: char) -> bool {
+    c.is_ascii_hexdigit() || c == '_'
+}
+
+fn is_opal_binary_digit(c: char) -> bool {
+    c == '0' || c == '1' || c == '_'
+}
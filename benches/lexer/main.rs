@@ -0,0 +1,124 @@
+//! Throughput comparison between the byte-oriented lexer and the `Vec<char>` based lexer it
+//! replaced. Run with `cargo bench --bench lexer`.
+
+mod char_lexer;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 20;
+
+/// Roughly what a hand-written BW8 program looks like.
+const PROGRAM: &str = "# Blink the status LED until the button is pressed.
+type char = u8;
+
+const STATUS_LED: u16 = 0xFF_04;
+const BUTTON_MASK: u8 = 0b0000_0001;
+
+struct Port {
+    data: u8,
+    direction: u8,
+}
+
+fn blink(port: Port*, delay: u16): u8 {
+    var count: u8 = 0;
+    while (port.data & BUTTON_MASK) == 0 {
+        port.data ^= 1 << 3;
+        count += 1;
+        wait(delay);
+    }
+    return count;
+}
+
+fn greet() {
+    print(\"Hello, BW8!\\n\", '\\t');
+}
+";
+
+/// A generated lookup table, which is mostly integer literals and commas.
+fn generated_table(entries: usize) -> String {
+    let mut table = String::from("const SINE: u8[256] = [\n");
+
+    for entry in 0..entries {
+        table.push_str(&format!("    0x{:02X}, {},\n", entry % 256, entry % 100));
+    }
+
+    table.push_str("];\n");
+    table
+}
+
+fn char_lexer_token_count(input: &str) -> usize {
+    let mut lexer = char_lexer::Lexer::new(input);
+    let mut count = 0;
+
+    loop {
+        match lexer.next_token() {
+            Ok(token) if token.kind == char_lexer::TokenKind::EOF => break,
+            Ok(token) => {
+                black_box(token);
+                count += 1;
+            }
+            Err(err) => panic!("char lexer failed: {:?}", err),
+        }
+    }
+
+    count
+}
+
+fn byte_lexer_token_count(input: &str) -> usize {
+    let mut lexer = opal::compiler::Lexer::new(input);
+    let mut count = 0;
+
+    loop {
+        match lexer.next_token() {
+            Ok(token) if token.kind == opal::compiler::TokenKind::EOF => break,
+            Ok(token) => {
+                black_box(token);
+                count += 1;
+            }
+            Err(err) => panic!("byte lexer failed: {:?}", err),
+        }
+    }
+
+    count
+}
+
+fn measure(input: &str, lex: fn(&str) -> usize) -> (usize, Duration) {
+    // Warm up once so both lexers start with the input in cache.
+    let tokens = lex(input);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        black_box(lex(black_box(input)));
+    }
+
+    (tokens, start.elapsed() / ITERATIONS)
+}
+
+fn report(name: &str, input: &str) {
+    let megabytes = input.len() as f64 / (1024.0 * 1024.0);
+
+    let (char_tokens, char_time) = measure(input, char_lexer_token_count);
+    let (byte_tokens, byte_time) = measure(input, byte_lexer_token_count);
+
+    assert_eq!(
+        char_tokens, byte_tokens,
+        "lexers disagree on the token count for {}",
+        name
+    );
+
+    println!(
+        "{:<10} {:>8.2} MiB {:>9} tokens | char lexer {:>8.2} MiB/s | byte lexer {:>8.2} MiB/s | {:.1}x",
+        name,
+        megabytes,
+        byte_tokens,
+        megabytes / char_time.as_secs_f64(),
+        megabytes / byte_time.as_secs_f64(),
+        char_time.as_secs_f64() / byte_time.as_secs_f64(),
+    );
+}
+
+fn main() {
+    report("program", &PROGRAM.repeat(2_000));
+    report("table", &generated_table(100_000));
+}
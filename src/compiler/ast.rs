@@ -0,0 +1,607 @@
+//! Typed views over the syntax tree. Each view wraps a `SyntaxNode` and reads its parts straight
+//! from the node's children and tokens, so casting is cheap and the view always reflects edits made
+//! to the tree. The views mirror the AST types of the parser and share their names, which is why
+//! this module is not re-exported from `compiler`.
+//!
+//! Accessors return `None` or skip parts that are missing from the tree, so a view can also be
+//! read from a node that no longer parses after an edit.
+
+use super::lexer::{Keyword, TokenKind};
+use super::parser::{AssignmentOperator, BinaryOperator, Linkage, UnaryOperator};
+use super::source_map::Span;
+use super::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+use std::fmt;
+
+/// A view of a syntax node of particular kinds.
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+
+    /// Wraps `node`, which must be of a kind accepted by `can_cast`.
+    fn from_syntax(node: SyntaxNode) -> Self;
+
+    fn syntax(&self) -> &SyntaxNode;
+
+    fn span(&self) -> Span {
+        self.syntax().span()
+    }
+}
+
+macro_rules! ast_node {
+    ($name:ident, $($kind:ident)|+) => {
+        #[derive(Debug, Clone)]
+        pub struct $name(SyntaxNode);
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $(SyntaxKind::$kind)|+)
+            }
+
+            fn from_syntax(node: SyntaxNode) -> Self {
+                Self(node)
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+ast_node!(SourceFile, SourceFile);
+ast_node!(
+    ConstantVariableDeclaration,
+    ConstantDeclaration | VariableDeclaration
+);
+ast_node!(FunctionDeclaration, FunctionDeclaration);
+ast_node!(StructUnionDeclaration, StructDeclaration | UnionDeclaration);
+ast_node!(EnumDeclaration, EnumDeclaration);
+ast_node!(EnumVariant, EnumVariant);
+ast_node!(TypeDeclaration, TypeDeclaration);
+ast_node!(Field, Field);
+ast_node!(AssignmentStatement, Assignment);
+ast_node!(IfStatement, If);
+ast_node!(WhileLoop, While);
+ast_node!(ForLoop, For);
+ast_node!(SwitchStatement, Switch);
+ast_node!(CaseStatement, Case);
+ast_node!(ReturnStatement, Return);
+ast_node!(Expression, Expression);
+ast_node!(FieldInitializer, FieldInitializer);
+ast_node!(Type, Type);
+
+/// The direct children of `node` that can be viewed as `T`.
+fn children<T: AstNode>(node: &SyntaxNode) -> Vec<T> {
+    node.children()
+        .into_iter()
+        .filter_map(|child| child.cast())
+        .collect()
+}
+
+fn child<T: AstNode>(node: &SyntaxNode) -> Option<T> {
+    node.children().into_iter().find_map(|child| child.cast())
+}
+
+/// The tokens directly under `node`, without trivia.
+fn tokens(node: &SyntaxNode) -> Vec<SyntaxToken> {
+    node.children_with_tokens()
+        .into_iter()
+        .filter_map(|element| match element {
+            SyntaxElement::Token(token) if !token.kind().is_trivia() => Some(token),
+            _ => None,
+        })
+        .collect()
+}
+
+fn has_token(node: &SyntaxNode, kind: TokenKind<'_>) -> bool {
+    tokens(node).iter().any(|token| token.kind() == kind)
+}
+
+/// The first identifier directly under `node`, which is the name it declares or refers to.
+fn identifier(node: &SyntaxNode) -> Option<String> {
+    tokens(node)
+        .into_iter()
+        .find_map(|token| match token.kind() {
+            TokenKind::Identifier(identifier) => Some(identifier.to_string()),
+            _ => None,
+        })
+}
+
+/// The statements of a block, or `None` if `node` has no block.
+fn block(node: &SyntaxNode) -> Option<Vec<BodyStatement>> {
+    match has_token(node, TokenKind::LeftBrace) {
+        false => None,
+        true => Some(children(node)),
+    }
+}
+
+/// A top-level declaration.
+#[derive(Debug, Clone)]
+pub enum Statement {
+    ConstantDeclaration(ConstantVariableDeclaration),
+    VariableDeclaration(ConstantVariableDeclaration),
+    FunctionDeclaration(FunctionDeclaration),
+    StructDeclaration(StructUnionDeclaration),
+    UnionDeclaration(StructUnionDeclaration),
+    EnumDeclaration(EnumDeclaration),
+    TypeDeclaration(TypeDeclaration),
+    /// Source that failed to parse and was skipped during error recovery.
+    Error(SyntaxNode),
+}
+
+impl AstNode for Statement {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            SyntaxKind::ConstantDeclaration
+                | SyntaxKind::VariableDeclaration
+                | SyntaxKind::FunctionDeclaration
+                | SyntaxKind::StructDeclaration
+                | SyntaxKind::UnionDeclaration
+                | SyntaxKind::EnumDeclaration
+                | SyntaxKind::TypeDeclaration
+                | SyntaxKind::Error
+        )
+    }
+
+    fn from_syntax(node: SyntaxNode) -> Self {
+        match node.kind() {
+            SyntaxKind::ConstantDeclaration => {
+                Statement::ConstantDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::VariableDeclaration => {
+                Statement::VariableDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::FunctionDeclaration => {
+                Statement::FunctionDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::StructDeclaration => {
+                Statement::StructDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::UnionDeclaration => Statement::UnionDeclaration(AstNode::from_syntax(node)),
+            SyntaxKind::EnumDeclaration => Statement::EnumDeclaration(AstNode::from_syntax(node)),
+            SyntaxKind::TypeDeclaration => Statement::TypeDeclaration(AstNode::from_syntax(node)),
+            _ => Statement::Error(node),
+        }
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Statement::ConstantDeclaration(decl) | Statement::VariableDeclaration(decl) => {
+                decl.syntax()
+            }
+            Statement::FunctionDeclaration(decl) => decl.syntax(),
+            Statement::StructDeclaration(decl) | Statement::UnionDeclaration(decl) => decl.syntax(),
+            Statement::EnumDeclaration(decl) => decl.syntax(),
+            Statement::TypeDeclaration(decl) => decl.syntax(),
+            Statement::Error(node) => node,
+        }
+    }
+}
+
+/// A statement inside a function body, as opposed to the top-level declarations of `Statement`.
+#[derive(Debug, Clone)]
+pub enum BodyStatement {
+    TypeDeclaration(TypeDeclaration),
+    ConstantDeclaration(ConstantVariableDeclaration),
+    VariableDeclaration(ConstantVariableDeclaration),
+    Assignment(AssignmentStatement),
+    If(IfStatement),
+    While(WhileLoop),
+    For(ForLoop),
+    Switch(SwitchStatement),
+    Break(SyntaxNode),
+    Continue(SyntaxNode),
+    Return(ReturnStatement),
+    /// Source that failed to parse and was skipped during error recovery.
+    Error(SyntaxNode),
+}
+
+impl AstNode for BodyStatement {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            SyntaxKind::TypeDeclaration
+                | SyntaxKind::ConstantDeclaration
+                | SyntaxKind::VariableDeclaration
+                | SyntaxKind::Assignment
+                | SyntaxKind::If
+                | SyntaxKind::While
+                | SyntaxKind::For
+                | SyntaxKind::Switch
+                | SyntaxKind::Break
+                | SyntaxKind::Continue
+                | SyntaxKind::Return
+                | SyntaxKind::Error
+        )
+    }
+
+    fn from_syntax(node: SyntaxNode) -> Self {
+        match node.kind() {
+            SyntaxKind::TypeDeclaration => {
+                BodyStatement::TypeDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::ConstantDeclaration => {
+                BodyStatement::ConstantDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::VariableDeclaration => {
+                BodyStatement::VariableDeclaration(AstNode::from_syntax(node))
+            }
+            SyntaxKind::Assignment => BodyStatement::Assignment(AstNode::from_syntax(node)),
+            SyntaxKind::If => BodyStatement::If(AstNode::from_syntax(node)),
+            SyntaxKind::While => BodyStatement::While(AstNode::from_syntax(node)),
+            SyntaxKind::For => BodyStatement::For(AstNode::from_syntax(node)),
+            SyntaxKind::Switch => BodyStatement::Switch(AstNode::from_syntax(node)),
+            SyntaxKind::Break => BodyStatement::Break(node),
+            SyntaxKind::Continue => BodyStatement::Continue(node),
+            SyntaxKind::Return => BodyStatement::Return(AstNode::from_syntax(node)),
+            _ => BodyStatement::Error(node),
+        }
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            BodyStatement::TypeDeclaration(decl) => decl.syntax(),
+            BodyStatement::ConstantDeclaration(decl) | BodyStatement::VariableDeclaration(decl) => {
+                decl.syntax()
+            }
+            BodyStatement::Assignment(statement) => statement.syntax(),
+            BodyStatement::If(statement) => statement.syntax(),
+            BodyStatement::While(statement) => statement.syntax(),
+            BodyStatement::For(statement) => statement.syntax(),
+            BodyStatement::Switch(statement) => statement.syntax(),
+            BodyStatement::Return(statement) => statement.syntax(),
+            BodyStatement::Break(node)
+            | BodyStatement::Continue(node)
+            | BodyStatement::Error(node) => node,
+        }
+    }
+}
+
+impl SourceFile {
+    pub fn statements(&self) -> Vec<Statement> {
+        children(&self.0)
+    }
+}
+
+impl ConstantVariableDeclaration {
+    pub fn is_var(&self) -> bool {
+        self.0.kind() == SyntaxKind::VariableDeclaration
+    }
+
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn const_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    /// `None` for variables declared without an initializer.
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+}
+
+impl FunctionDeclaration {
+    pub fn linkage(&self) -> Linkage {
+        match has_token(&self.0, TokenKind::Keyword(Keyword::Extern)) {
+            false => Linkage::Internal,
+            true => Linkage::External,
+        }
+    }
+
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn parameters(&self) -> Vec<Field> {
+        children(&self.0)
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        has_token(&self.0, TokenKind::Ellipsis)
+    }
+
+    /// Parameter types are inside their `Field`s, so the only type directly under the function is
+    /// the return type.
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    /// `None` for prototypes, which end in `;` rather than a body.
+    pub fn body(&self) -> Option<Vec<BodyStatement>> {
+        block(&self.0)
+    }
+}
+
+impl StructUnionDeclaration {
+    pub fn is_union(&self) -> bool {
+        self.0.kind() == SyntaxKind::UnionDeclaration
+    }
+
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    /// `None` for opaque forward declarations such as `struct Foo;`.
+    pub fn fields(&self) -> Option<Vec<Field>> {
+        match has_token(&self.0, TokenKind::LeftBrace) {
+            false => None,
+            true => Some(children(&self.0)),
+        }
+    }
+}
+
+impl EnumDeclaration {
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    /// `None` for opaque forward declarations such as `enum Foo;`.
+    pub fn variants(&self) -> Option<Vec<EnumVariant>> {
+        match has_token(&self.0, TokenKind::LeftBrace) {
+            false => None,
+            true => Some(children(&self.0)),
+        }
+    }
+}
+
+impl EnumVariant {
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    /// The explicit discriminant, if one was given.
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+}
+
+impl TypeDeclaration {
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl Field {
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn field_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl AssignmentStatement {
+    pub fn target(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+
+    pub fn operator(&self) -> Option<AssignmentOperator> {
+        tokens(&self.0)
+            .iter()
+            .find_map(|token| AssignmentOperator::from_token(&token.kind()))
+    }
+
+    pub fn value(&self) -> Option<Expression> {
+        children(&self.0).into_iter().nth(1)
+    }
+}
+
+impl IfStatement {
+    pub fn condition(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Vec<BodyStatement> {
+        self.branches().0
+    }
+
+    pub fn else_body(&self) -> Option<Vec<BodyStatement>> {
+        self.branches().1
+    }
+
+    /// Splits the statements of the node at its `else`.
+    fn branches(&self) -> (Vec<BodyStatement>, Option<Vec<BodyStatement>>) {
+        let mut body = Vec::new();
+        let mut else_body = None;
+
+        for element in self.0.children_with_tokens() {
+            match element {
+                SyntaxElement::Token(token)
+                    if token.kind() == TokenKind::Keyword(Keyword::Else) =>
+                {
+                    else_body = Some(Vec::new())
+                }
+                SyntaxElement::Token(_) => (),
+                SyntaxElement::Node(node) => {
+                    if let Some(statement) = node.cast() {
+                        else_body.as_mut().unwrap_or(&mut body).push(statement);
+                    }
+                }
+            }
+        }
+
+        (body, else_body)
+    }
+}
+
+impl WhileLoop {
+    pub fn condition(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Vec<BodyStatement> {
+        children(&self.0)
+    }
+}
+
+impl ForLoop {
+    pub fn variable(&self) -> Option<Field> {
+        child(&self.0)
+    }
+
+    pub fn start(&self) -> Option<Expression> {
+        children(&self.0).into_iter().next()
+    }
+
+    pub fn end(&self) -> Option<Expression> {
+        children(&self.0).into_iter().nth(1)
+    }
+
+    pub fn step(&self) -> Option<Expression> {
+        children(&self.0).into_iter().nth(2)
+    }
+
+    pub fn body(&self) -> Vec<BodyStatement> {
+        children(&self.0)
+    }
+}
+
+impl SwitchStatement {
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+
+    pub fn cases(&self) -> Vec<CaseStatement> {
+        children(&self.0)
+    }
+}
+
+impl CaseStatement {
+    /// `None` for the `default` case.
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Vec<BodyStatement> {
+        children(&self.0)
+    }
+}
+
+impl ReturnStatement {
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+}
+
+impl Expression {
+    /// The expressions directly inside this one, such as the operands of an operator, the callee
+    /// and arguments of a call, or the elements of an array literal.
+    pub fn operands(&self) -> Vec<Expression> {
+        children(&self.0)
+    }
+
+    /// The type of a cast or `sizeof`.
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    /// The name of an identifier, the member of a member access or the struct of a struct literal.
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn fields(&self) -> Vec<FieldInitializer> {
+        children(&self.0)
+    }
+
+    /// The operator of a unary expression: the first token before the operand, other than the
+    /// opening parenthesis of a parenthesized expression.
+    pub fn unary_operator(&self) -> Option<UnaryOperator> {
+        let before: Vec<SyntaxToken> = self
+            .0
+            .children_with_tokens()
+            .into_iter()
+            .map_while(|element| match element {
+                SyntaxElement::Token(token) => Some(token),
+                SyntaxElement::Node(_) => None,
+            })
+            .collect();
+
+        let kind = before
+            .iter()
+            .map(SyntaxToken::kind)
+            .find(|kind| !kind.is_trivia() && *kind != TokenKind::LeftParenthesis)?;
+        UnaryOperator::from_token(&kind)
+    }
+
+    /// The operator of a binary expression: the first token after the left operand.
+    pub fn binary_operator(&self) -> Option<BinaryOperator> {
+        let after: Vec<SyntaxToken> = self
+            .0
+            .children_with_tokens()
+            .into_iter()
+            .skip_while(|element| matches!(element, SyntaxElement::Token(_)))
+            .skip(1)
+            .map_while(|element| match element {
+                SyntaxElement::Token(token) => Some(token),
+                SyntaxElement::Node(_) => None,
+            })
+            .collect();
+
+        let kind = after
+            .iter()
+            .map(SyntaxToken::kind)
+            .find(|kind| !kind.is_trivia())?;
+        BinaryOperator::from_token(&kind)
+    }
+}
+
+impl FieldInitializer {
+    pub fn identifier(&self) -> Option<String> {
+        identifier(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+}
+
+impl Type {
+    /// The name of a primitive or named type.
+    pub fn name(&self) -> Option<String> {
+        match self.0.children().is_empty() {
+            true => tokens(&self.0)
+                .first()
+                .map(|token| token.text().to_string()),
+            false => None,
+        }
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        match tokens(&self.0).last() {
+            Some(token) => token.kind() == TokenKind::Asterisk,
+            None => false,
+        }
+    }
+
+    /// The pointee of a pointer, the element of an array, or the parameters and then the return
+    /// type of a function type.
+    pub fn types(&self) -> Vec<Type> {
+        children(&self.0)
+    }
+
+    /// The size of an array type.
+    pub fn size(&self) -> Option<Expression> {
+        child(&self.0)
+    }
+}
+
+/// Views print as the source text of their node, formatting and comments included.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
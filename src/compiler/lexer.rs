@@ -1,3 +1,13 @@
+use super::source_map::{FileId, Span};
+use std::fmt;
+
+/// A position in the source text. All fields are 0-based.
+///
+/// - `absolute` is the UTF-8 byte offset into the input.
+/// - `line` counts line breaks before the position. Both `\n` and `\r\n` are one line break.
+/// - `column` is the display column within the line: every character is one column wide, except
+///   that a tab advances to the next multiple of the tab width (see `Lexer::with_tab_width`) and
+///   the `\r` of a `\r\n` takes up no space.
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct TextPosition {
     pub absolute: usize,
@@ -25,6 +35,7 @@ pub enum Keyword {
     I16,
     Fn,
     SizeOf,
+    As,
     Type,
     Const,
     Var,
@@ -44,6 +55,39 @@ pub enum Keyword {
     Enum,
 }
 
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = match self {
+            Keyword::U8 => "u8",
+            Keyword::I8 => "i8",
+            Keyword::U16 => "u16",
+            Keyword::I16 => "i16",
+            Keyword::Fn => "fn",
+            Keyword::SizeOf => "sizeof",
+            Keyword::As => "as",
+            Keyword::Type => "type",
+            Keyword::Const => "const",
+            Keyword::Var => "var",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::While => "while",
+            Keyword::For => "for",
+            Keyword::Switch => "switch",
+            Keyword::Case => "case",
+            Keyword::Default => "default",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Return => "return",
+            Keyword::Extern => "extern",
+            Keyword::Struct => "struct",
+            Keyword::Union => "union",
+            Keyword::Enum => "enum",
+        };
+
+        f.write_str(keyword)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum IntegerLiteralKind {
     Decimal,
@@ -51,27 +95,43 @@ pub enum IntegerLiteralKind {
     Binary,
 }
 
+/// Whitespace or a comment. Trivia means nothing to the parser, so it is attached to the
+/// neighbouring significant tokens rather than appearing in the token stream itself.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
-    pub kind: TokenKind,
+pub struct Trivia<'a> {
+    pub kind: TokenKind<'a>,
     pub position: TokenPosition,
 }
 
-impl Token {
-    pub fn new(kind: TokenKind, position: TokenPosition) -> Self {
-        Self { kind, position }
-    }
+/// A significant token along with the trivia around it. Leading trivia is everything between the
+/// previous token's trailing trivia and this token. Trailing trivia is everything after this token
+/// up to, but not including, the next line break.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub position: TokenPosition,
+    pub leading_trivia: Vec<Trivia<'a>>,
+    pub trailing_trivia: Vec<Trivia<'a>>,
+}
 
-    pub fn keyword(key: Keyword, position: TokenPosition) -> Self {
+impl<'a> Token<'a> {
+    pub fn new(kind: TokenKind<'a>, position: TokenPosition) -> Self {
         Self {
-            kind: TokenKind::Keyword(key),
+            kind,
             position,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         }
     }
-    pub fn from_kind(kind: TokenKind) -> Self {
-        Self {
+
+    pub fn keyword(key: Keyword, position: TokenPosition) -> Self {
+        Self::new(TokenKind::Keyword(key), position)
+    }
+
+    pub fn from_kind(kind: TokenKind<'a>) -> Self {
+        Self::new(
             kind,
-            position: TokenPosition {
+            TokenPosition {
                 start: TextPosition {
                     absolute: 0,
                     line: 0,
@@ -83,44 +143,52 @@ impl Token {
                     column: 0,
                 },
             },
-        }
+        )
     }
 
-    pub fn from_kinds(kinds: &Vec<TokenKind>) -> Vec<Self> {
+    pub fn from_kinds(kinds: &[TokenKind<'a>]) -> Vec<Self> {
         kinds
             .iter()
             .map(|kind| Self::from_kind(kind.clone()))
             .collect()
     }
+
+    /// The source text of this token and all of its trivia.
+    pub fn to_source(&self, source: &str) -> String {
+        let mut result = String::new();
+
+        for trivia in self.leading_trivia.iter() {
+            result.push_str(&source[trivia.position.start.absolute..trivia.position.end.absolute]);
+        }
+
+        result.push_str(&source[self.position.start.absolute..self.position.end.absolute]);
+
+        for trivia in self.trailing_trivia.iter() {
+            result.push_str(&source[trivia.position.start.absolute..trivia.position.end.absolute]);
+        }
+
+        result
+    }
 }
 
-impl Default for Token {
+impl Default for Token<'_> {
     fn default() -> Self {
-        Self {
-            kind: TokenKind::Illegal,
-            position: TokenPosition {
-                start: TextPosition {
-                    absolute: 0,
-                    line: 0,
-                    column: 0,
-                },
-                end: TextPosition {
-                    absolute: 0,
-                    line: 0,
-                    column: 0,
-                },
-            },
-        }
+        Self::from_kind(TokenKind::Illegal)
     }
 }
 
+/// Identifiers, comments and integer literal spellings borrow from the source text. String
+/// literals are owned since decoding their escape sequences may change them.
+///
+/// Char and string literals hold bytes, as BW8 has no wider characters. The source characters in
+/// them must be ASCII, while `\x80` to `\xFF` escapes give the bytes above it.
 #[derive(Debug, PartialEq, Clone)]
-pub enum TokenKind {
+pub enum TokenKind<'a> {
     Keyword(Keyword),
-    Identifier(String),
-    IntegerLiteral(IntegerLiteralKind, u16),
-    CharLiteral(char),
-    StringLiteral(String),
+    Identifier(&'a str),
+    IntegerLiteral(IntegerLiteralKind, u32, &'a str),
+    CharLiteral(u8),
+    StringLiteral(Vec<u8>),
     LeftBracket,
     RightBracket,
     LeftParenthesis,
@@ -161,28 +229,162 @@ pub enum TokenKind {
     ForwardSlash,
     Percent,
     Dot,
+    Ellipsis,
     Whitespace(usize),
-    Comment(String),
+    Comment(&'a str),
+    BlockComment(&'a str),
+    DocComment(&'a str),
     EOF,
     Illegal,
 }
 
+/// Displays a token as it is spelled in source, for use in diagnostics. Char and string literals
+/// are shown with their escapes re-applied, and tokens without a fixed spelling (whitespace,
+/// comments, `EOF` and `Illegal`) are described in words instead.
+impl fmt::Display for TokenKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TokenKind::*;
+
+        let token = match self {
+            Keyword(keyword) => return write!(f, "{}", keyword),
+            Identifier(identifier) => identifier,
+            IntegerLiteral(_, _, literal) => literal,
+            CharLiteral(c) => {
+                f.write_str("'")?;
+                write_escaped(f, *c, b'\'')?;
+                return f.write_str("'");
+            }
+            StringLiteral(literal) => {
+                f.write_str("\"")?;
+                for &c in literal {
+                    write_escaped(f, c, b'"')?;
+                }
+                return f.write_str("\"");
+            }
+            LeftBracket => "[",
+            RightBracket => "]",
+            LeftParenthesis => "(",
+            RightParenthesis => ")",
+            LeftBrace => "{",
+            RightBrace => "}",
+            LeftChevron => "<<",
+            RightChevron => ">>",
+            Colon => ":",
+            SemiColon => ";",
+            Comma => ",",
+            Plus => "+",
+            Minus => "-",
+            Bang => "!",
+            Ampersand => "&",
+            DoubleAmpersand => "&&",
+            Bar => "|",
+            DoubleBar => "||",
+            Caret => "^",
+            Equal => "=",
+            DoubleEqual => "==",
+            BangEqual => "!=",
+            LessThan => "<",
+            LessThanEqual => "<=",
+            GreaterThan => ">",
+            GreaterThanEqual => ">=",
+            PlusEqual => "+=",
+            MinusEqual => "-=",
+            AsteriskEqual => "*=",
+            ForwardSlashEqual => "/=",
+            PercentEqual => "%=",
+            LeftChevronEqual => "<<=",
+            RightChevronEqual => ">>=",
+            AmpersandEqual => "&=",
+            BarEqual => "|=",
+            CaretEqual => "^=",
+            Asterisk => "*",
+            ForwardSlash => "/",
+            Percent => "%",
+            Dot => ".",
+            Ellipsis => "...",
+            Whitespace(_) => "whitespace",
+            Comment(_) | BlockComment(_) => "comment",
+            DocComment(_) => "doc comment",
+            EOF => "end of file",
+            Illegal => "illegal token",
+        };
+
+        f.write_str(token)
+    }
+}
+
+impl TokenKind<'_> {
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace(_)
+                | TokenKind::Comment(_)
+                | TokenKind::BlockComment(_)
+                | TokenKind::DocComment(_)
+        )
+    }
+}
+
+/// Folds the trivia in a raw token stream, such as the one produced by `Lexer`, into the
+/// `leading_trivia` and `trailing_trivia` of the significant tokens around it.
+pub fn attach_trivia<'a>(raw: impl IntoIterator<Item = Token<'a>>) -> Vec<Token<'a>> {
+    let mut result: Vec<Token<'a>> = Vec::new();
+    let mut leading = Vec::new();
+
+    // Trivia goes to the previous token until the first line break, and to the next one after.
+    let mut trailing_open = false;
+
+    for token in raw {
+        if token.kind.is_trivia() {
+            if let TokenKind::Whitespace(line_breaks) = token.kind {
+                if line_breaks > 0 {
+                    trailing_open = false;
+                }
+            }
+
+            let trivia = Trivia {
+                kind: token.kind,
+                position: token.position,
+            };
+
+            match result.last_mut() {
+                Some(previous) if trailing_open => previous.trailing_trivia.push(trivia),
+                _ => leading.push(trivia),
+            }
+        } else {
+            result.push(Token {
+                leading_trivia: std::mem::take(&mut leading),
+                ..token
+            });
+            trailing_open = true;
+        }
+    }
+
+    result
+}
+
 #[derive(Debug, PartialEq)]
 pub enum LexErrorKind {
     UnexpectedEOF,
-    InputExhausted,
     UnrecognizedCharacter,
     MultipleCharLiteral,
     EmptyCharLiteral,
     EmptyStringLiteral,
+    EmptyIntegerLiteral,
+    IntegerLiteralOutOfRange,
+    UnknownEscapeSequence,
     NonconformingLexeme,
+    NonAsciiCharacter,
+    UnterminatedLiteral,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct LexError {
     pub kind: LexErrorKind,
     msg: String,
+    file: FileId,
     position: TextPosition,
+    end: TextPosition,
 }
 
 impl LexError {
@@ -190,496 +392,735 @@ impl LexError {
         LexError {
             kind,
             msg: String::from("Not given"),
+            file: FileId::default(),
             position,
+            end: position,
         }
     }
 
     pub fn with_msg(self, msg: String) -> Self {
-        return Self {
-            kind: self.kind,
-            position: self.position,
-            msg,
-        };
+        return Self { msg, ..self };
+    }
+
+    /// Extends the error to cover the whole offending lexeme rather than just its first character.
+    pub fn with_end(self, end: TextPosition) -> Self {
+        return Self { end, ..self };
+    }
+
+    pub fn get_msg(&self) -> &str {
+        return &self.msg;
     }
 
     pub fn get_position(&self) -> TextPosition {
         return self.position;
     }
+
+    pub fn get_span(&self) -> TokenPosition {
+        return TokenPosition::new(self.position, self.end);
+    }
+
+    /// Records the file the error was found in. The lexer does this for every error it returns.
+    pub fn in_file(self, file: FileId) -> Self {
+        return Self { file, ..self };
+    }
+
+    pub fn get_file(&self) -> FileId {
+        return self.file;
+    }
+
+    /// The offending lexeme as a `Span` within its file, to be resolved with a `SourceMap`.
+    pub fn span(&self) -> Span {
+        return self.get_span().to_span(self.file);
+    }
+}
+
+/// How large an integer literal the lexer accepts before reporting `IntegerLiteralOutOfRange`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegerRange {
+    /// Literals must fit in a `u16`, the widest BW8 integer type.
+    Native,
+    /// Literals may use the full `u32` range. The parser is then responsible for range checking
+    /// against the target type, which lets it fold a leading `-` into the value first (`-32768`).
+    Wide,
+}
+
+impl IntegerRange {
+    fn max(self) -> u32 {
+        match self {
+            IntegerRange::Native => u16::MAX as u32,
+            IntegerRange::Wide => u32::MAX,
+        }
+    }
 }
 
 pub type LexResult<T> = Result<T, LexError>;
 
-pub struct Lexer {
-    input: Vec<char>,
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// The column after `c`, given the column that `c` starts at. See `TextPosition` for the model.
+pub fn next_column(column: usize, c: char, next: Option<char>, tab_width: usize) -> usize {
+    match (c, next) {
+        ('\n', _) => 0,
+        ('\r', Some('\n')) => column,
+        ('\t', _) => (column / tab_width + 1) * tab_width,
+        _ => column + 1,
+    }
+}
+
+pub struct Lexer<'a> {
+    input: &'a str,
+    file: FileId,
     position: TextPosition,
+    tab_width: usize,
+    integer_range: IntegerRange,
+    errors: Vec<LexError>,
+    finished: bool,
 }
 
-impl Lexer {
-    pub fn new(input: &str) -> Self {
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
         Lexer {
-            input: String::from(input).chars().collect(),
+            input,
+            file: FileId::default(),
             position: TextPosition {
                 absolute: 0,
                 line: 0,
                 column: 0,
             },
+            tab_width: DEFAULT_TAB_WIDTH,
+            integer_range: IntegerRange::Native,
+            errors: Vec::new(),
+            finished: false,
         }
     }
 
-    pub fn next_token(&mut self) -> LexResult<Token> {
-        use LexErrorKind::*;
+    /// Sets the file that the spans of lexical errors refer to.
+    pub fn with_file(self, file: FileId) -> Self {
+        Self { file, ..self }
+    }
 
-        if self.current().is_none() {
-            return Err(LexError::new(InputExhausted, self.position));
-        }
+    /// Panics if `tab_width` is zero.
+    pub fn with_tab_width(self, tab_width: usize) -> Self {
+        assert!(tab_width > 0, "tab width must be at least 1");
+        Self { tab_width, ..self }
+    }
 
-        match self.next_whitespace() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                UnrecognizedCharacter => return Err(err),
-                _ => (),
-            },
+    pub fn with_integer_range(self, integer_range: IntegerRange) -> Self {
+        Self {
+            integer_range,
+            ..self
         }
+    }
 
-        match self.next_comment() {
-            Ok(token) => return Ok(token),
-            Err(err) => {
-                match err.kind {
-                    // UnexpectedEOF => todo!(),
-                    // InputExhausted => todo!(),
-                    // UnrecognizedCharacter => todo!(),
-                    // MultipleCharLiteral => todo!(),
-                    // EmptyCharLiteral => todo!(),
-                    // EmptyStringLiteral => todo!(),
-                    // NonconformingLexeme => todo!(),
-                    _ => (),
-                }
+    /// Lexes the next token like the `Iterator` implementation, but never produces an error. The
+    /// error is recorded instead (see `take_errors`) and a `TokenKind::Illegal` token spanning the
+    /// skipped lexeme is returned in its place.
+    pub fn next_token_recovering(&mut self) -> Token<'a> {
+        let start = self.position;
+
+        match self.next() {
+            Some(Ok(token)) => token,
+            Some(Err(err)) => {
+                self.errors.push(err);
+                Token::new(TokenKind::Illegal, TokenPosition::new(start, self.position))
             }
+            None => Token::new(
+                TokenKind::EOF,
+                TokenPosition::new(self.position, self.position),
+            ),
         }
+    }
 
-        match self.next_integer_literal() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                _ => (),
-            },
-        }
-
-        match self.next_char_literal() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                UnrecognizedCharacter => return Err(err),
-                MultipleCharLiteral => return Err(err),
-                EmptyCharLiteral => return Err(err),
-                _ => (),
-            },
-        }
+    /// Returns the errors recorded by `next_token_recovering` so far.
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
 
-        match self.next_string_literal() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                UnrecognizedCharacter => return Err(err),
-                EmptyStringLiteral => return Err(err),
-                _ => (),
-            },
-        }
+    /// Lexes the next token, or a `TokenKind::EOF` token once the input is exhausted. A bad lexeme
+    /// is not necessarily skipped; use the `Iterator` implementation to keep lexing past errors.
+    pub fn next_token(&mut self) -> LexResult<Token<'a>> {
+        self.next_lexeme().map_err(|err| err.in_file(self.file))
+    }
 
-        match self.next_identifier_or_keyword() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                UnrecognizedCharacter => return Err(err),
-                _ => (),
-            },
+    fn next_lexeme(&mut self) -> LexResult<Token<'a>> {
+        // The first byte of a lexeme is enough to decide which kind of token it must be.
+        match self.current() {
+            None => Ok(Token::new(
+                TokenKind::EOF,
+                TokenPosition::new(self.position, self.position),
+            )),
+            Some(c) if c.is_ascii_whitespace() => Ok(self.next_whitespace()),
+            Some(b'#') => self.next_comment(),
+            Some(c) if c.is_ascii_digit() => self.next_integer_literal(),
+            Some(b'\'') => self.next_char_literal(),
+            Some(b'"') => self.next_string_literal(),
+            Some(c) if is_ident_head(c) => Ok(self.next_identifier_or_keyword()),
+            Some(_) => self.next_primitive_token(),
         }
+    }
 
-        match self.next_primitive_token() {
-            Ok(token) => return Ok(token),
-            Err(err) => match err.kind {
-                UnexpectedEOF => return Err(err),
-                UnrecognizedCharacter => return Err(err),
-                _ => (),
-            },
-        }
+    fn current(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position.absolute).copied()
+    }
 
-        LexResult::Err(
-            LexError::new(LexErrorKind::UnrecognizedCharacter, self.position).with_msg(format!(
-                "Unrecognized character '{}'",
-                self.current().unwrap()
-            )),
-        )
+    fn peek(&self) -> Option<u8> {
+        self.input
+            .as_bytes()
+            .get(self.position.absolute + 1)
+            .copied()
     }
 
-    fn current(&self) -> Option<char> {
-        match self.input.get(self.position.absolute) {
-            Some(char_ref) => Some(*char_ref),
-            None => None,
-        }
+    fn current_char(&self) -> Option<char> {
+        self.input[self.position.absolute..].chars().next()
     }
 
     fn advance(&mut self) {
         match self.current() {
             None => (),
-            Some(char) => {
-                if char == '\n' {
+            Some(byte) => {
+                let c = match byte {
+                    0x00..=0x7F => byte as char,
+                    _ => self.current_char().unwrap(),
+                };
+
+                if c == '\n' {
                     self.position.line += 1;
-                    self.position.column = 0;
                 }
-                self.position.absolute += 1;
-                self.position.column += 1;
+
+                self.position.column = next_column(
+                    self.position.column,
+                    c,
+                    self.peek().map(char::from),
+                    self.tab_width,
+                );
+
+                // Step over a whole UTF-8 sequence so that `absolute` stays on a char boundary.
+                self.position.absolute += c.len_utf8();
             }
         }
     }
 
-    fn advance_by(&mut self, by: usize) -> String {
-        let mut result = String::new();
-        let mut count = by;
+    fn advance_by(&mut self, by: usize) -> &'a str {
+        let start = self.position.absolute;
 
-        while let Some(c) = self.current() {
-            if count > 0 {
-                self.advance();
-                result.push(c);
-            } else {
-                break;
-            }
-            count -= 1;
+        for _ in 0..by {
+            self.advance();
         }
 
-        result
+        &self.input[start..self.position.absolute]
     }
 
-    fn advance_while(&mut self, predicate: fn(char) -> bool) -> Vec<char> {
-        let mut result = Vec::new();
+    fn advance_while(&mut self, predicate: fn(u8) -> bool) -> &'a str {
+        let start = self.position.absolute;
 
         while let Some(c) = self.current() {
             if predicate(c) {
                 self.advance();
-                result.push(c);
             } else {
                 break;
             }
         }
 
-        result
+        &self.input[start..self.position.absolute]
     }
 
-    fn next_whitespace(&mut self) -> LexResult<Token> {
-        let start = self.position.clone();
+    fn next_whitespace(&mut self) -> Token<'a> {
+        let start = self.position;
 
         let consumed = self.advance_while(|c| c.is_ascii_whitespace());
 
-        match consumed.len() {
-            0 => LexResult::Err(LexError::new(
-                LexErrorKind::NonconformingLexeme,
-                self.position,
-            )),
-            _ => LexResult::Ok(Token::new(
-                TokenKind::Whitespace(
-                    consumed
-                        .into_iter()
-                        .filter(|&c| c == '\n')
-                        .collect::<String>()
-                        .len(),
-                ),
-                TokenPosition::new(start, self.position),
-            )),
-        }
+        Token::new(
+            TokenKind::Whitespace(consumed.bytes().filter(|&c| c == b'\n').count()),
+            TokenPosition::new(start, self.position),
+        )
     }
 
-    fn next_comment(&mut self) -> LexResult<Token> {
-        use LexErrorKind::{NonconformingLexeme, UnexpectedEOF};
-        use TokenKind::Comment;
+    fn next_comment(&mut self) -> LexResult<Token<'a>> {
+        let start = self.position;
 
-        let start = self.position.clone();
-        let mut comment: Vec<char>;
+        match self.peek() {
+            Some(b'[') => return self.next_block_comment(),
+            Some(b'#') => {
+                self.advance_by(2);
+                let comment = self.advance_while(|c| c != b'\n');
 
-        match self.current() {
-            None => Err(LexError::new(UnexpectedEOF, self.position)),
-            Some('#') => {
+                Ok(Token::new(
+                    TokenKind::DocComment(comment),
+                    TokenPosition::new(start, self.position),
+                ))
+            }
+            _ => {
                 self.advance();
-                comment = self.advance_while(|c| c != '\n');
+                let comment = self.advance_while(|c| c != b'\n');
+
                 Ok(Token::new(
-                    Comment(comment.iter().collect()),
-                    TokenPosition {
-                        start: start,
-                        end: self.position,
-                    },
+                    TokenKind::Comment(comment),
+                    TokenPosition::new(start, self.position),
                 ))
             }
-            Some(c) => {
-                Err(LexError::new(NonconformingLexeme, self.position)
-                    .with_msg(format!("Expected `#`, found {}", c)))
+        }
+    }
+
+    /// Lexes a `#[ ... ]#` comment. Block comments nest, so each `#[` inside one must be closed
+    /// by its own `]#` before the comment ends.
+    fn next_block_comment(&mut self) -> LexResult<Token<'a>> {
+        let start = self.position;
+        let mut depth = 0;
+
+        loop {
+            match (self.current(), self.peek()) {
+                (None, _) => {
+                    return Err(LexError::new(LexErrorKind::UnexpectedEOF, start)
+                        .with_end(self.position)
+                        .with_msg(String::from(
+                            "Unexpected EOF when searching for `]#` to close block comment",
+                        )))
+                }
+                (Some(b'#'), Some(b'[')) => {
+                    depth += 1;
+                    self.advance_by(2);
+                }
+                (Some(b']'), Some(b'#')) => {
+                    depth -= 1;
+                    self.advance_by(2);
+
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => self.advance(),
             }
         }
+
+        let comment = &self.input[start.absolute + 2..self.position.absolute - 2];
+
+        Ok(Token::new(
+            TokenKind::BlockComment(comment),
+            TokenPosition::new(start, self.position),
+        ))
     }
 
-    fn next_integer_literal(&mut self) -> LexResult<Token> {
-        let start = self.position.clone();
+    fn next_integer_literal(&mut self) -> LexResult<Token<'a>> {
+        use IntegerLiteralKind::{Binary, Decimal, Hexadecimal};
+
+        let start = self.position;
+
+        // "0x" and "0b" prefixes select the radix. Anything else is decimal.
+        let (kind, radix, is_digit): (IntegerLiteralKind, u32, fn(u8) -> bool) =
+            match (self.current(), self.peek()) {
+                (Some(b'0'), Some(b'x')) => (Hexadecimal, 16, is_opal_hexadecimal_digit),
+                (Some(b'0'), Some(b'b')) => (Binary, 2, is_opal_binary_digit),
+                _ => (Decimal, 10, is_opal_decimal_digit),
+            };
 
-        let consumed = self.advance_while(|c| -> bool { is_opal_decimal_digit(c) });
-        let non_underscore: Vec<char> = consumed.into_iter().filter(|&c| c != '_').collect();
+        if kind != Decimal {
+            self.advance_by(2);
+        }
+
+        let digits = self.advance_while(is_digit);
+
+        // A letter or digit straight after the literal, as in `0b102` or `0x1G`, is a digit that
+        // doesn't belong to the radix. Report the whole lexeme rather than splitting it in two.
+        if self.current().is_some_and(is_ident_tail) {
+            self.advance_while(is_ident_tail);
+
+            return Err(LexError::new(LexErrorKind::NonconformingLexeme, start)
+                .with_end(self.position)
+                .with_msg(format!(
+                    "Integer literal `{}` contains characters that are not {} digits",
+                    &self.input[start.absolute..self.position.absolute],
+                    match kind {
+                        Decimal => "decimal",
+                        Hexadecimal => "hexadecimal",
+                        Binary => "binary",
+                    }
+                )));
+        }
 
-        match non_underscore.len() {
-            0 => Err(LexError::new(
-                LexErrorKind::UnrecognizedCharacter,
-                self.position,
+        let spelling = &self.input[start.absolute..self.position.absolute];
+
+        let mut digit_count = 0;
+        let mut literal: Option<u32> = Some(0);
+
+        for digit in digits.bytes().filter(|&c| c != b'_') {
+            digit_count += 1;
+            literal = literal
+                .and_then(|value| value.checked_mul(radix))
+                .and_then(|value| value.checked_add((digit as char).to_digit(radix).unwrap()));
+        }
+
+        match (digit_count, literal) {
+            (0, _) => Err(
+                LexError::new(LexErrorKind::EmptyIntegerLiteral, start).with_msg(format!(
+                    "Integer literal `{}` has no digits after its prefix",
+                    spelling
+                )),
+            ),
+            (_, Some(literal)) if literal <= self.integer_range.max() => Ok(Token::new(
+                TokenKind::IntegerLiteral(kind, literal, spelling),
+                TokenPosition::new(start, self.position),
             )),
-            _ => {
-                let literal: u16 = non_underscore.iter().collect::<String>().parse().unwrap();
-                Ok(Token::new(
-                    TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, literal),
-                    TokenPosition::new(start, self.position),
-                ))
-            }
+            _ => Err(LexError::new(LexErrorKind::IntegerLiteralOutOfRange, start)
+                .with_end(self.position)
+                .with_msg(format!(
+                    "Integer literal `{}` exceeds the maximum of {}",
+                    spelling,
+                    self.integer_range.max()
+                ))),
         }
     }
 
-    fn next_char_literal(&mut self) -> LexResult<Token> {
-        use LexErrorKind::{
-            EmptyCharLiteral, MultipleCharLiteral, NonconformingLexeme, UnexpectedEOF,
-        };
+    fn next_char_literal(&mut self) -> LexResult<Token<'a>> {
+        use LexErrorKind::{EmptyCharLiteral, MultipleCharLiteral};
         use TokenKind::CharLiteral;
 
-        let start = self.position.clone();
-        let mut literal = Vec::new();
+        let start = self.position;
+        let mut literal = None;
+        let mut length = 0;
+        let mut content_error = None;
 
-        match self.current() {
-            None => return Err(LexError::new(UnexpectedEOF, self.position)),
-            Some('\'') => loop {
-                self.advance();
+        self.advance();
 
-                match self.current() {
-                    None => {
-                        return Err(LexError::new(UnexpectedEOF, self.position)
-                            .with_msg(String::from("Unexpected EOF when searching for `'`")))
+        loop {
+            match self.current() {
+                None | Some(b'\n') => return Err(self.unterminated_literal(start, '\'')),
+                Some(b'\'') => break,
+                // A bad escape or character is only reported once the closing `'` is found, so that
+                // the whole literal is skipped rather than just the bad part.
+                Some(b'\\') => match self.next_escape_sequence() {
+                    Ok(c) => {
+                        literal.get_or_insert(c);
+                        length += 1;
+                    }
+                    Err(err) => {
+                        content_error.get_or_insert(err);
                     }
-                    Some('\'') => break,
-                    Some(c) => literal.push(c),
+                },
+                Some(c) if !c.is_ascii() => {
+                    content_error.get_or_insert(self.next_non_ascii_character());
+                    length += 1;
+                }
+                Some(c) => {
+                    literal.get_or_insert(c);
+                    length += 1;
+                    self.advance();
                 }
-            },
-            Some(c) => {
-                return Err(LexError::new(NonconformingLexeme, self.position)
-                    .with_msg(format!("Expected `'`, found {}", c)))
             }
         }
 
-        match literal.len() {
-            1 => Ok(Token::new(
-                CharLiteral(*literal.get(0).unwrap()),
+        self.advance();
+
+        if let Some(err) = content_error {
+            return Err(err);
+        }
+
+        match (length, literal) {
+            (1, Some(c)) => Ok(Token::new(
+                CharLiteral(c),
                 TokenPosition::new(start, self.position),
             )),
-            0 => {
-                self.position = start;
-                Err(LexError::new(EmptyCharLiteral, start)
-                    .with_msg(format!("Character literal may not be empty.")))
-            }
-            n => {
-                self.position = start;
-                Err(LexError::new(MultipleCharLiteral, start).with_msg(format!(
+            (0, _) => Err(LexError::new(EmptyCharLiteral, start)
+                .with_end(self.position)
+                .with_msg(String::from("Character literal may not be empty."))),
+            (n, _) => Err(LexError::new(MultipleCharLiteral, start)
+                .with_end(self.position)
+                .with_msg(format!(
                     "Character literal may only contain 1 character. Found {}",
                     n
-                )))
-            }
+                ))),
         }
     }
 
-    fn next_string_literal(&mut self) -> LexResult<Token> {
-        use LexErrorKind::UnexpectedEOF;
+    fn next_string_literal(&mut self) -> LexResult<Token<'a>> {
+        let start = self.position;
+        let mut literal = Vec::new();
+        let mut content_error = None;
 
-        let start = self.position.clone();
-        let mut literal: Vec<char> = Vec::new();
+        self.advance();
 
-        match self.current() {
-            None => Err(LexError::new(UnexpectedEOF, self.position)),
-            Some('"') => {
-                self.advance();
-                loop {
-                    match self.current() {
-                        None => {
-                            return Err(LexError::new(LexErrorKind::UnexpectedEOF, self.position))
-                        }
-                        Some('"') => {
-                            let rv = Ok(Token::new(
-                                TokenKind::StringLiteral(literal.into_iter().collect::<String>()),
-                                TokenPosition::new(start, self.position),
-                            ));
-                            self.advance();
-                            return rv;
-                        }
-                        Some(c) => {
-                            self.advance();
-                            literal.push(c);
-                        }
+        loop {
+            // Copy everything up to the next quote, escape, newline or non-ASCII character in one go.
+            literal.extend_from_slice(
+                self.advance_while(|c| c != b'"' && c != b'\\' && c != b'\n' && c.is_ascii())
+                    .as_bytes(),
+            );
+
+            match self.current() {
+                None | Some(b'\n') => return Err(self.unterminated_literal(start, '"')),
+                Some(b'"') => {
+                    self.advance();
+
+                    return match content_error {
+                        Some(err) => Err(err),
+                        None => Ok(Token::new(
+                            TokenKind::StringLiteral(literal),
+                            TokenPosition::new(start, self.position),
+                        )),
+                    };
+                }
+                Some(b'\\') => match self.next_escape_sequence() {
+                    Ok(c) => literal.push(c),
+                    Err(err) => {
+                        content_error.get_or_insert(err);
                     }
+                },
+                Some(_) => {
+                    content_error.get_or_insert(self.next_non_ascii_character());
                 }
             }
-            Some(_) => Err(LexError::new(
-                LexErrorKind::NonconformingLexeme,
-                self.position,
-            )),
         }
     }
 
-    fn next_identifier_or_keyword(&mut self) -> LexResult<Token> {
-        use LexErrorKind::UnexpectedEOF;
+    /// The error for a char or string literal that reaches the end of its line or of the input
+    /// without being closed, spanning from its opening quote. A newline is left in place so that
+    /// lexing picks up again on the next line.
+    fn unterminated_literal(&self, start: TextPosition, quote: char) -> LexError {
+        LexError::new(LexErrorKind::UnterminatedLiteral, start)
+            .with_end(self.position)
+            .with_msg(format!("Missing closing `{}`", quote))
+    }
 
-        let start = self.position.clone();
-        let mut token = String::new();
+    /// Skips the non-ASCII character at the current position, which can't be stored in a byte,
+    /// and returns the error for it.
+    fn next_non_ascii_character(&mut self) -> LexError {
+        let start = self.position;
+        let c = self.current_char().unwrap();
+        self.advance();
+
+        LexError::new(LexErrorKind::NonAsciiCharacter, start)
+            .with_end(self.position)
+            .with_msg(format!(
+                "Character `{}` in literal is not ASCII; use a `\\x` escape for bytes above 0x7F",
+                c
+            ))
+    }
 
-        match self.current() {
-            None => return Err(LexError::new(UnexpectedEOF, self.position)),
-            Some(c) => {
-                if !is_ident_head(c) {
-                    self.position = start;
-                    return Err(LexError::new(
-                        LexErrorKind::NonconformingLexeme,
-                        self.position,
-                    ));
-                } else {
-                    token.push(c);
-                    self.advance();
-                }
-            }
-        }
+    /// Decodes the escape sequence starting at the current `\`, leaving the lexer just past it.
+    fn next_escape_sequence(&mut self) -> LexResult<u8> {
+        use LexErrorKind::{UnexpectedEOF, UnknownEscapeSequence};
 
-        loop {
-            match self.current() {
-                None => return self.to_identifier_or_keyword(start, token.as_str()),
-                Some(c) => {
-                    if !is_ident_tail(c) {
-                        return self.to_identifier_or_keyword(start, token.as_str());
+        let start = self.position;
+        self.advance();
+
+        let escaped = match self.current_char() {
+            None => {
+                return Err(LexError::new(UnexpectedEOF, self.position)
+                    .with_msg(String::from("Unexpected EOF in escape sequence")))
+            }
+            // Leave the newline for the literal to report as unterminated.
+            Some('\n') => {
+                return Err(LexError::new(UnknownEscapeSequence, start)
+                    .with_end(self.position)
+                    .with_msg(String::from("Escape sequence may not span lines")))
+            }
+            Some(c) => c,
+        };
+        self.advance();
+
+        match escaped {
+            'b' => Ok(0x08),
+            'f' => Ok(0x0C),
+            'n' => Ok(b'\n'),
+            'r' => Ok(b'\r'),
+            't' => Ok(b'\t'),
+            '\\' => Ok(b'\\'),
+            '\'' => Ok(b'\''),
+            '"' => Ok(b'"'),
+            'x' => {
+                let digits_start = self.position.absolute;
+
+                while self.position.absolute - digits_start < 2 {
+                    match self.current() {
+                        Some(c) if c.is_ascii_hexdigit() => self.advance(),
+                        _ => break,
                     }
+                }
 
-                    token.push(c);
-                    self.advance();
+                let digits = &self.input[digits_start..self.position.absolute];
+
+                match u8::from_str_radix(digits, 16) {
+                    Ok(byte) if digits.len() == 2 => Ok(byte),
+                    _ => Err(LexError::new(UnknownEscapeSequence, start)
+                        .with_end(self.position)
+                        .with_msg(format!(
+                            "Escape sequence `\\x{}` must have exactly two hexadecimal digits",
+                            digits
+                        ))),
                 }
             }
+            c => Err(LexError::new(UnknownEscapeSequence, start)
+                .with_end(self.position)
+                .with_msg(format!("Unknown escape sequence `\\{}`", c))),
         }
     }
 
-    fn to_identifier_or_keyword(&self, start: TextPosition, input: &str) -> LexResult<Token> {
+    fn next_identifier_or_keyword(&mut self) -> Token<'a> {
+        let start = self.position;
+
+        let identifier = self.advance_while(is_ident_tail);
+
+        self.to_identifier_or_keyword(start, identifier)
+    }
+
+    fn to_identifier_or_keyword(&self, start: TextPosition, input: &'a str) -> Token<'a> {
         let position = TokenPosition {
             start,
             end: self.position,
         };
 
         match input {
-            "u8" => Ok(Token::keyword(Keyword::U8, position)),
-            "i8" => Ok(Token::keyword(Keyword::I8, position)),
-            "u16" => Ok(Token::keyword(Keyword::U16, position)),
-            "i16" => Ok(Token::keyword(Keyword::I16, position)),
-            "fn" => Ok(Token::keyword(Keyword::Fn, position)),
-            "sizeof" => Ok(Token::keyword(Keyword::SizeOf, position)),
-            "type" => Ok(Token::keyword(Keyword::Type, position)),
-            "const" => Ok(Token::keyword(Keyword::Const, position)),
-            "var" => Ok(Token::keyword(Keyword::Var, position)),
-            "if" => Ok(Token::keyword(Keyword::If, position)),
-            "else" => Ok(Token::keyword(Keyword::Else, position)),
-            "while" => Ok(Token::keyword(Keyword::While, position)),
-            "for" => Ok(Token::keyword(Keyword::For, position)),
-            "switch" => Ok(Token::keyword(Keyword::Switch, position)),
-            "case" => Ok(Token::keyword(Keyword::Case, position)),
-            "default" => Ok(Token::keyword(Keyword::Default, position)),
-            "break" => Ok(Token::keyword(Keyword::Break, position)),
-            "continue" => Ok(Token::keyword(Keyword::Continue, position)),
-            "return" => Ok(Token::keyword(Keyword::Return, position)),
-            "extern" => Ok(Token::keyword(Keyword::Extern, position)),
-            "struct" => Ok(Token::keyword(Keyword::Struct, position)),
-            "union" => Ok(Token::keyword(Keyword::Union, position)),
-            "enum" => Ok(Token::keyword(Keyword::Enum, position)),
-            ident => Ok(Token::new(
-                TokenKind::Identifier(String::from(ident)),
-                position,
-            )),
+            "u8" => Token::keyword(Keyword::U8, position),
+            "i8" => Token::keyword(Keyword::I8, position),
+            "u16" => Token::keyword(Keyword::U16, position),
+            "i16" => Token::keyword(Keyword::I16, position),
+            "fn" => Token::keyword(Keyword::Fn, position),
+            "sizeof" => Token::keyword(Keyword::SizeOf, position),
+            "as" => Token::keyword(Keyword::As, position),
+            "type" => Token::keyword(Keyword::Type, position),
+            "const" => Token::keyword(Keyword::Const, position),
+            "var" => Token::keyword(Keyword::Var, position),
+            "if" => Token::keyword(Keyword::If, position),
+            "else" => Token::keyword(Keyword::Else, position),
+            "while" => Token::keyword(Keyword::While, position),
+            "for" => Token::keyword(Keyword::For, position),
+            "switch" => Token::keyword(Keyword::Switch, position),
+            "case" => Token::keyword(Keyword::Case, position),
+            "default" => Token::keyword(Keyword::Default, position),
+            "break" => Token::keyword(Keyword::Break, position),
+            "continue" => Token::keyword(Keyword::Continue, position),
+            "return" => Token::keyword(Keyword::Return, position),
+            "extern" => Token::keyword(Keyword::Extern, position),
+            "struct" => Token::keyword(Keyword::Struct, position),
+            "union" => Token::keyword(Keyword::Union, position),
+            "enum" => Token::keyword(Keyword::Enum, position),
+            ident => Token::new(TokenKind::Identifier(ident), position),
         }
     }
 
-    fn next_primitive_token(&mut self) -> LexResult<Token> {
-        use LexErrorKind::NonconformingLexeme;
-
-        const PATTERNS: [(&str, TokenKind); 40] = [
-            ("<<=", TokenKind::LeftChevronEqual),
-            (">>=", TokenKind::RightChevronEqual),
-            ("<<", TokenKind::LeftChevron),
-            (">>", TokenKind::RightChevron),
-            ("+=", TokenKind::PlusEqual),
-            ("-=", TokenKind::MinusEqual),
-            ("*=", TokenKind::AsteriskEqual),
-            ("/=", TokenKind::ForwardSlashEqual),
-            ("%=", TokenKind::PercentEqual),
-            ("&=", TokenKind::AmpersandEqual),
-            ("|=", TokenKind::BarEqual),
-            ("^=", TokenKind::CaretEqual),
-            ("==", TokenKind::DoubleEqual),
-            ("!=", TokenKind::BangEqual),
-            ("<=", TokenKind::LessThanEqual),
-            (">=", TokenKind::GreaterThanEqual),
-            ("&&", TokenKind::DoubleAmpersand),
-            ("||", TokenKind::DoubleBar),
-            ("+", TokenKind::Plus),
-            ("-", TokenKind::Minus),
-            ("!", TokenKind::Bang),
-            ("&", TokenKind::Ampersand),
-            ("|", TokenKind::Bar),
-            ("^", TokenKind::Caret),
-            ("=", TokenKind::Equal),
-            ("<", TokenKind::LessThan),
-            (">", TokenKind::GreaterThan),
-            ("*", TokenKind::Asterisk),
-            ("/", TokenKind::ForwardSlash),
-            ("%", TokenKind::Percent),
-            (".", TokenKind::Dot),
-            ("[", TokenKind::LeftBracket),
-            ("]", TokenKind::RightBracket),
-            ("(", TokenKind::LeftParenthesis),
-            (")", TokenKind::RightParenthesis),
-            ("{", TokenKind::LeftBrace),
-            ("}", TokenKind::RightBrace),
-            (":", TokenKind::Colon),
-            (";", TokenKind::SemiColon),
-            (",", TokenKind::Comma),
-        ];
-
-        let start = self.position.clone();
-
-        for (pattern, token) in PATTERNS.iter() {
-            if let Ok(_) = self.next_match(pattern) {
-                return Ok(Token::new(
-                    token.clone(),
-                    TokenPosition::new(start, self.position),
-                ));
+    fn next_primitive_token(&mut self) -> LexResult<Token<'a>> {
+        use TokenKind::*;
+
+        let start = self.position;
+
+        // Longer operators are listed before their prefixes so that the first match is the
+        // longest one.
+        let (kind, length) = match &self.input.as_bytes()[start.absolute..] {
+            [b'<', b'<', b'=', ..] => (LeftChevronEqual, 3),
+            [b'>', b'>', b'=', ..] => (RightChevronEqual, 3),
+            [b'<', b'<', ..] => (LeftChevron, 2),
+            [b'>', b'>', ..] => (RightChevron, 2),
+            [b'<', b'=', ..] => (LessThanEqual, 2),
+            [b'>', b'=', ..] => (GreaterThanEqual, 2),
+            [b'<', ..] => (LessThan, 1),
+            [b'>', ..] => (GreaterThan, 1),
+            [b'+', b'=', ..] => (PlusEqual, 2),
+            [b'+', ..] => (Plus, 1),
+            [b'-', b'=', ..] => (MinusEqual, 2),
+            [b'-', ..] => (Minus, 1),
+            [b'*', b'=', ..] => (AsteriskEqual, 2),
+            [b'*', ..] => (Asterisk, 1),
+            [b'/', b'=', ..] => (ForwardSlashEqual, 2),
+            [b'/', ..] => (ForwardSlash, 1),
+            [b'%', b'=', ..] => (PercentEqual, 2),
+            [b'%', ..] => (Percent, 1),
+            [b'&', b'=', ..] => (AmpersandEqual, 2),
+            [b'&', b'&', ..] => (DoubleAmpersand, 2),
+            [b'&', ..] => (Ampersand, 1),
+            [b'|', b'=', ..] => (BarEqual, 2),
+            [b'|', b'|', ..] => (DoubleBar, 2),
+            [b'|', ..] => (Bar, 1),
+            [b'^', b'=', ..] => (CaretEqual, 2),
+            [b'^', ..] => (Caret, 1),
+            [b'=', b'=', ..] => (DoubleEqual, 2),
+            [b'=', ..] => (Equal, 1),
+            [b'!', b'=', ..] => (BangEqual, 2),
+            [b'!', ..] => (Bang, 1),
+            [b'.', b'.', b'.', ..] => (Ellipsis, 3),
+            [b'.', ..] => (Dot, 1),
+            [b'[', ..] => (LeftBracket, 1),
+            [b']', ..] => (RightBracket, 1),
+            [b'(', ..] => (LeftParenthesis, 1),
+            [b')', ..] => (RightParenthesis, 1),
+            [b'{', ..] => (LeftBrace, 1),
+            [b'}', ..] => (RightBrace, 1),
+            [b':', ..] => (Colon, 1),
+            [b';', ..] => (SemiColon, 1),
+            [b',', ..] => (Comma, 1),
+            _ => {
+                return Err(
+                    LexError::new(LexErrorKind::UnrecognizedCharacter, self.position).with_msg(
+                        format!("Unrecognized character '{}'", self.current_char().unwrap()),
+                    ),
+                )
             }
-        }
+        };
+
+        self.advance_by(length);
 
-        Err(LexError::new(NonconformingLexeme, self.position))
+        Ok(Token::new(kind, TokenPosition::new(start, self.position)))
     }
+}
+
+/// Lexes tokens lazily, ending with a single `TokenKind::EOF` token. Lexing continues past a bad
+/// lexeme: its error is yielded and the lexeme skipped.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = LexResult<Token<'a>>;
 
-    fn next_match(&mut self, pattern: &str) -> LexResult<()> {
-        use LexErrorKind::NonconformingLexeme;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
 
-        let start = self.position.clone();
-        let s = self.advance_by(pattern.chars().count());
+        let start = self.position;
+        let result = self.next_token();
 
-        if s.eq(pattern) {
-            Ok(())
-        } else {
-            self.position = start;
-            Err(LexError::new(NonconformingLexeme, self.position))
+        match &result {
+            Ok(token) => self.finished = token.kind == TokenKind::EOF,
+            Err(_) => {
+                // Every bad lexeme must be skipped, even the ones that leave the lexer where
+                // they started, or else lexing would never make progress.
+                if self.position == start {
+                    self.advance();
+                }
+            }
         }
+
+        Some(result)
     }
 }
 
-fn is_ident_head(c: char) -> bool {
-    c.is_ascii_alphabetic() || c == '_'
+/// Writes `c` as it would appear in a char or string literal delimited by `quote`, escaping it if
+/// needed so that it lexes back to the same byte.
+pub fn write_escaped(f: &mut fmt::Formatter<'_>, c: u8, quote: u8) -> fmt::Result {
+    match c {
+        0x08 => f.write_str("\\b"),
+        0x0C => f.write_str("\\f"),
+        b'\n' => f.write_str("\\n"),
+        b'\r' => f.write_str("\\r"),
+        b'\t' => f.write_str("\\t"),
+        b'\\' => f.write_str("\\\\"),
+        c if c == quote => write!(f, "\\{}", c as char),
+        c if c.is_ascii_graphic() || c == b' ' => write!(f, "{}", c as char),
+        c => write!(f, "\\x{:02X}", c),
+    }
+}
+
+fn is_ident_head(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
 }
 
-fn is_ident_tail(c: char) -> bool {
+fn is_ident_tail(c: u8) -> bool {
     is_ident_head(c) || c.is_ascii_digit()
 }
 
-fn is_opal_decimal_digit(c: char) -> bool {
-    c.is_digit(10) || c == '_'
+fn is_opal_decimal_digit(c: u8) -> bool {
+    c.is_ascii_digit() || c == b'_'
+}
+
+fn is_opal_hexadecimal_digit(c: u8) -> bool {
+    c.is_ascii_hexdigit() || c == b'_'
+}
+
+fn is_opal_binary_digit(c: u8) -> bool {
+    c == b'0' || c == b'1' || c == b'_'
 }
@@ -1,8 +1,16 @@
+pub mod ast;
 pub mod lexer;
+pub mod operators;
 pub mod parser;
+pub mod printer;
+pub mod source_map;
+pub mod syntax;
 
 pub use lexer::*;
+pub use operators::*;
 pub use parser::*;
+pub use source_map::*;
+pub use syntax::*;
 
 #[derive(Debug)]
 pub enum CompilerError {
@@ -10,8 +18,8 @@ pub enum CompilerError {
     ParseError(parser::ParseError),
 }
 
-impl Into<()> for CompilerError {
-    fn into(self) -> () {}
+impl From<CompilerError> for () {
+    fn from(_: CompilerError) -> Self {}
 }
 
 impl From<lexer::LexError> for CompilerError {
@@ -26,51 +34,77 @@ impl From<parser::ParseError> for CompilerError {
     }
 }
 
-pub type CompilerResult<T> = Result<T, (CompilerError, T)>;
+pub type CompilerResult<T> = Result<T, (Vec<CompilerError>, T)>;
 
-pub fn tokenize(input: &str) -> CompilerResult<Vec<Token>> {
-    let mut lexer = Lexer::new(input);
+/// The significant tokens ending in `TokenKind::EOF`, with whitespace and comments attached to them
+/// as trivia. On failure, every lexical error alongside that stream with each bad lexeme replaced
+/// by a `TokenKind::Illegal` token.
+pub type TokenizeResult<'a> = Result<Vec<Token<'a>>, (Vec<LexError>, Vec<Token<'a>>)>;
+
+pub fn tokenize(file: FileId, input: &str) -> TokenizeResult<'_> {
+    let mut lexer = Lexer::new(input).with_file(file);
     let mut result = Vec::new();
 
     loop {
-        match lexer.next_token() {
-            Ok(token) => result.push(token),
-            Err(error) => {
-                if let LexErrorKind::InputExhausted = error.kind {
-                    return Ok(result);
-                } else {
-                    return Err((CompilerError::from(error), result));
-                }
-            }
+        let token = lexer.next_token_recovering();
+        let is_eof = token.kind == TokenKind::EOF;
+
+        result.push(token);
+
+        if is_eof {
+            break;
         }
     }
+
+    let result = attach_trivia(result);
+    let errors = lexer.take_errors();
+
+    match errors.len() {
+        0 => Ok(result),
+        _ => Err((errors, result)),
+    }
 }
 
-// Result<Vec<Declaration>, (Vec<CompilerError>, Vec<Declaration)>
-pub fn parse(tokens: Vec<Token>) -> CompilerResult<Vec<Statement>> {
-    let mut parser = Parser::new(tokens);
+/// Parses every declaration in `tokens`. Syntax errors don't stop parsing: each one is recovered
+/// from and leaves a `Statement::Error` in the result, so that all of them can be reported at once
+/// alongside the partial AST.
+pub fn parse(file: FileId, tokens: Vec<Token<'_>>) -> CompilerResult<Vec<Statement>> {
+    let mut parser = Parser::new(file, tokens);
     let mut result = Vec::new();
 
-    loop {
-        match parser.parse_declaration() {
-            ParseResult::Ok((statement, _)) => result.push(statement),
-            ParseResult::Err(err) => match err {
-                ParseError {
-                    kind: ParseErrorKind::UnexpectedToken,
-                    msg: _,
-                    line: _,
-                    column: _,
-                } => break CompilerResult::Err((err.into(), result)),
-                ParseError {
-                    kind: ParseErrorKind::UnexpectedEOF,
-                    msg: _,
-                    line: _,
-                    column: _,
-                } => match parser.is_exhausted() {
-                    true => break CompilerResult::Ok(result),
-                    false => break CompilerResult::Err((err.into(), result)),
-                },
-            },
+    while !parser.is_exhausted() {
+        result.push(parser.parse_declaration_recovering());
+    }
+
+    let errors: Vec<CompilerError> = parser.take_errors().into_iter().map(Into::into).collect();
+
+    match errors.len() {
+        0 => Ok(result),
+        _ => Err((errors, result)),
+    }
+}
+
+/// Parses `source` into a lossless syntax tree, which keeps every token, comment and bit of
+/// whitespace so that the source can be edited without losing its formatting. Like `parse`, this
+/// recovers from errors and always returns a tree, alongside any lexical and syntax errors.
+pub fn parse_syntax(file: FileId, source: &str) -> CompilerResult<SyntaxNode> {
+    let (mut errors, tokens): (Vec<CompilerError>, _) = match tokenize(file, source) {
+        Ok(tokens) => (Vec::new(), tokens),
+        Err((errors, tokens)) => (errors.into_iter().map(Into::into).collect(), tokens),
+    };
+
+    let statements = match parse(file, tokens.clone()) {
+        Ok(statements) => statements,
+        Err((parse_errors, statements)) => {
+            errors.extend(parse_errors);
+            statements
         }
+    };
+
+    let tree = build_syntax_tree(file, source, &tokens, &statements);
+
+    match errors.len() {
+        0 => Ok(tree),
+        _ => Err((errors, tree)),
     }
 }
@@ -0,0 +1,211 @@
+use super::lexer::TokenKind;
+use super::parser::{AssignmentOperator, BinaryOperator, UnaryOperator};
+use std::fmt;
+
+/// Which side of a chain of operators with the same precedence groups first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Unary(UnaryOperator),
+    Binary(BinaryOperator),
+    Assignment(AssignmentOperator),
+}
+
+/// An entry in the operator table. Higher precedences bind tighter.
+#[derive(Debug)]
+pub struct OperatorInfo {
+    pub operator: Operator,
+    pub token: TokenKind<'static>,
+    pub precedence: u8,
+    pub associativity: Associativity,
+}
+
+/// Postfix calls, indexing and member accesses, as well as every primary expression.
+pub const POSTFIX_PRECEDENCE: u8 = 12;
+/// Prefix unary operators and casts.
+pub const UNARY_PRECEDENCE: u8 = 11;
+/// Assignment is a statement rather than an expression, so it binds loosest of all.
+pub const ASSIGNMENT_PRECEDENCE: u8 = 0;
+
+const fn unary(operator: UnaryOperator, token: TokenKind<'static>) -> OperatorInfo {
+    OperatorInfo {
+        operator: Operator::Unary(operator),
+        token,
+        precedence: UNARY_PRECEDENCE,
+        associativity: Associativity::Right,
+    }
+}
+
+const fn binary(
+    operator: BinaryOperator,
+    token: TokenKind<'static>,
+    precedence: u8,
+) -> OperatorInfo {
+    OperatorInfo {
+        operator: Operator::Binary(operator),
+        token,
+        precedence,
+        associativity: Associativity::Left,
+    }
+}
+
+const fn assignment(operator: AssignmentOperator, token: TokenKind<'static>) -> OperatorInfo {
+    OperatorInfo {
+        operator: Operator::Assignment(operator),
+        token,
+        precedence: ASSIGNMENT_PRECEDENCE,
+        associativity: Associativity::Right,
+    }
+}
+
+/// Every operator in the language. The parser, the pretty-printer and diagnostics all read this
+/// table, so adding an operator only takes a new entry here and a variant for it in the AST.
+///
+/// The binary precedences follow C.
+pub static OPERATORS: &[OperatorInfo] = &[
+    unary(UnaryOperator::Plus, TokenKind::Plus),
+    unary(UnaryOperator::Negate, TokenKind::Minus),
+    unary(UnaryOperator::Not, TokenKind::Bang),
+    unary(UnaryOperator::AddressOf, TokenKind::Ampersand),
+    unary(UnaryOperator::Dereference, TokenKind::Asterisk),
+    binary(BinaryOperator::Multiply, TokenKind::Asterisk, 10),
+    binary(BinaryOperator::Divide, TokenKind::ForwardSlash, 10),
+    binary(BinaryOperator::Modulo, TokenKind::Percent, 10),
+    binary(BinaryOperator::Add, TokenKind::Plus, 9),
+    binary(BinaryOperator::Subtract, TokenKind::Minus, 9),
+    binary(BinaryOperator::ShiftLeft, TokenKind::LeftChevron, 8),
+    binary(BinaryOperator::ShiftRight, TokenKind::RightChevron, 8),
+    binary(BinaryOperator::LessThan, TokenKind::LessThan, 7),
+    binary(BinaryOperator::LessThanEqual, TokenKind::LessThanEqual, 7),
+    binary(BinaryOperator::GreaterThan, TokenKind::GreaterThan, 7),
+    binary(
+        BinaryOperator::GreaterThanEqual,
+        TokenKind::GreaterThanEqual,
+        7,
+    ),
+    binary(BinaryOperator::Equal, TokenKind::DoubleEqual, 6),
+    binary(BinaryOperator::NotEqual, TokenKind::BangEqual, 6),
+    binary(BinaryOperator::BitwiseAnd, TokenKind::Ampersand, 5),
+    binary(BinaryOperator::BitwiseXor, TokenKind::Caret, 4),
+    binary(BinaryOperator::BitwiseOr, TokenKind::Bar, 3),
+    binary(BinaryOperator::LogicalAnd, TokenKind::DoubleAmpersand, 2),
+    binary(BinaryOperator::LogicalOr, TokenKind::DoubleBar, 1),
+    assignment(AssignmentOperator::Assign, TokenKind::Equal),
+    assignment(AssignmentOperator::AddAssign, TokenKind::PlusEqual),
+    assignment(AssignmentOperator::SubtractAssign, TokenKind::MinusEqual),
+    assignment(AssignmentOperator::MultiplyAssign, TokenKind::AsteriskEqual),
+    assignment(
+        AssignmentOperator::DivideAssign,
+        TokenKind::ForwardSlashEqual,
+    ),
+    assignment(AssignmentOperator::ModuloAssign, TokenKind::PercentEqual),
+    assignment(
+        AssignmentOperator::ShiftLeftAssign,
+        TokenKind::LeftChevronEqual,
+    ),
+    assignment(
+        AssignmentOperator::ShiftRightAssign,
+        TokenKind::RightChevronEqual,
+    ),
+    assignment(
+        AssignmentOperator::BitwiseAndAssign,
+        TokenKind::AmpersandEqual,
+    ),
+    assignment(AssignmentOperator::BitwiseOrAssign, TokenKind::BarEqual),
+    assignment(AssignmentOperator::BitwiseXorAssign, TokenKind::CaretEqual),
+];
+
+impl OperatorInfo {
+    pub fn of(operator: Operator) -> &'static OperatorInfo {
+        OPERATORS
+            .iter()
+            .find(|info| info.operator == operator)
+            .expect("every operator has an entry in the operator table")
+    }
+
+    /// The operators a token can stand for. A token such as `-` can be both a unary and a binary
+    /// operator.
+    pub fn for_token<'a>(
+        kind: &'a TokenKind<'_>,
+    ) -> impl Iterator<Item = &'static OperatorInfo> + 'a {
+        OPERATORS.iter().filter(move |info| info.token == *kind)
+    }
+}
+
+impl Operator {
+    pub fn info(self) -> &'static OperatorInfo {
+        OperatorInfo::of(self)
+    }
+
+    pub fn precedence(self) -> u8 {
+        self.info().precedence
+    }
+
+    pub fn associativity(self) -> Associativity {
+        self.info().associativity
+    }
+}
+
+impl UnaryOperator {
+    pub fn from_token(kind: &TokenKind<'_>) -> Option<Self> {
+        OperatorInfo::for_token(kind).find_map(|info| match info.operator {
+            Operator::Unary(operator) => Some(operator),
+            _ => None,
+        })
+    }
+}
+
+impl BinaryOperator {
+    pub fn from_token(kind: &TokenKind<'_>) -> Option<Self> {
+        OperatorInfo::for_token(kind).find_map(|info| match info.operator {
+            Operator::Binary(operator) => Some(operator),
+            _ => None,
+        })
+    }
+
+    pub fn precedence(self) -> u8 {
+        Operator::Binary(self).precedence()
+    }
+
+    pub fn associativity(self) -> Associativity {
+        Operator::Binary(self).associativity()
+    }
+}
+
+impl AssignmentOperator {
+    pub fn from_token(kind: &TokenKind<'_>) -> Option<Self> {
+        OperatorInfo::for_token(kind).find_map(|info| match info.operator {
+            Operator::Assignment(operator) => Some(operator),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info().token)
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Operator::Unary(*self).fmt(f)
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Operator::Binary(*self).fmt(f)
+    }
+}
+
+impl fmt::Display for AssignmentOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Operator::Assignment(*self).fmt(f)
+    }
+}
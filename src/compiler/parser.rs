@@ -1,102 +1,209 @@
-use super::{IntegerLiteralKind, Keyword, Token, TokenKind, TokenPosition};
+use std::fmt;
+
+use super::{
+    Associativity, FileId, IntegerLiteralKind, Keyword, Operator, OperatorInfo, Span, TextPosition,
+    Token, TokenKind, TokenPosition, Trivia,
+};
 
 #[derive(Debug)]
 pub struct ParseError {
     pub kind: ParseErrorKind,
     pub msg: String,
-    pub line: usize,
-    pub column: usize,
+    /// The span of the offending token.
+    pub span: Span,
+    /// Everything the parser would have accepted in place of the offending token.
+    pub expected: Vec<Expected>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum ParseErrorKind {
     UnexpectedToken,
     UnexpectedEOF,
+    UninitializedConstant,
+    AmbiguousCast,
+}
+
+/// Something that the parser tried to match, for use in error messages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    Token(TokenKind<'static>),
+    /// A grammar production that can start with many different tokens, such as "expression".
+    Production(&'static str),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Token(kind) => write!(f, "`{}`", kind),
+            Expected::Production(production) => f.write_str(production),
+        }
+    }
+}
+
+impl ParseError {
+    /// An error at the parser's current token, listing everything that was tried there, as in
+    /// "expected `:` or `=`, found `;`".
+    pub fn unexpected(parser: &Parser<'_>) -> Self {
+        let index = parser.input.skip_trivia(parser.input.index);
+
+        let expected = if index == parser.expected_index {
+            parser.expected.clone()
+        } else {
+            Vec::new()
+        };
+
+        let (kind, found, span) = match parser.input.tokens.get(index) {
+            Some(token) if token.kind != TokenKind::EOF => (
+                ParseErrorKind::UnexpectedToken,
+                describe_token(&token.kind),
+                parser.span(&token.position),
+            ),
+            token => {
+                // Without an EOF token, point just past the last token instead.
+                let position = match token {
+                    Some(token) => token.position.clone(),
+                    None => parser.input.prev().unwrap_or_default().position,
+                };
+                let end = position.end.absolute;
+
+                (
+                    ParseErrorKind::UnexpectedEOF,
+                    describe_token(&TokenKind::EOF),
+                    Span::new(parser.file, end, end),
+                )
+            }
+        };
+
+        let msg = match expected.split_last() {
+            None => format!("unexpected {}", found),
+            Some((last, [])) => format!("expected {}, found {}", last, found),
+            Some((last, rest)) => {
+                let rest: Vec<String> = rest.iter().map(|expected| expected.to_string()).collect();
+
+                format!("expected {} or {}, found {}", rest.join(", "), last, found)
+            }
+        };
+
+        Self {
+            kind,
+            msg,
+            span,
+            expected,
+        }
+    }
 }
 
 impl ParseError {
-    pub fn unexpected_token(parser: &Parser) -> Self {
+    /// A `const` declaration without a value, spanning the declaration up to where the value
+    /// should be.
+    pub fn uninitialized_constant(identifier: &str, span: Span) -> Self {
+        Self {
+            kind: ParseErrorKind::UninitializedConstant,
+            msg: format!("constant `{}` must be given a value", identifier),
+            span,
+            expected: vec![Expected::Token(TokenKind::Equal)],
+        }
+    }
+
+    /// A `*` after an `as` type that could either make the type a pointer or multiply by an operand
+    /// starting with `next`, spanning from the `*` to `next`.
+    pub fn ambiguous_cast(next: &TokenKind<'_>, span: Span) -> Self {
         Self {
-            kind: ParseErrorKind::UnexpectedToken,
+            kind: ParseErrorKind::AmbiguousCast,
             msg: format!(
-                "Unexpected token {:?}",
-                parser.input.clone().prev().unwrap_or_default()
+                "`*` after a cast type followed by {} is ambiguous; parenthesize the cast or the \
+                 multiplication",
+                describe_token(next)
             ),
-            line: parser
-                .input
-                .clone()
-                .prev()
-                .unwrap_or_default()
-                .position
-                .start
-                .line,
-            column: parser
-                .input
-                .clone()
-                .prev()
-                .unwrap_or_default()
-                .position
-                .start
-                .column,
+            span,
+            expected: Vec::new(),
         }
     }
+}
 
-    pub fn from_kind(parser: &Parser, kind: ParseErrorKind) -> Self {
-        Self {
-            kind,
-            msg: format!("Parsing error: {:?}", kind),
-            line: parser
-                .input
-                .clone()
-                .prev()
-                .unwrap_or_default()
-                .position
-                .start
-                .line,
-            column: parser
-                .input
-                .clone()
-                .prev()
-                .unwrap_or_default()
-                .position
-                .start
-                .column,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+fn describe_token(kind: &TokenKind<'_>) -> String {
+    match kind {
+        TokenKind::Identifier(_) => format!("identifier `{}`", kind),
+        TokenKind::IntegerLiteral(..) => format!("integer literal `{}`", kind),
+        TokenKind::CharLiteral(_) => format!("char literal `{}`", kind),
+        TokenKind::StringLiteral(_) => format!("string literal `{}`", kind),
+        TokenKind::EOF | TokenKind::Illegal => kind.to_string(),
+        _ if OperatorInfo::for_token(kind)
+            .any(|info| !matches!(info.operator, Operator::Assignment(_))) =>
+        {
+            format!("operator `{}`", kind)
         }
+        _ => format!("`{}`", kind),
     }
 }
 
 #[derive(Debug)]
 pub struct ConstantVariableDeclaration {
-    is_var: bool,
-    identifier: String,
-    const_type: Type,
-    value: Expression,
+    pub is_var: bool,
+    pub identifier: String,
+    pub const_type: Type,
+    /// `None` for variables declared without an initializer, which are placed in RAM (`.bss`)
+    /// rather than in ROM data. Constants always have a value.
+    pub value: Option<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Linkage {
+    /// Defined or forward declared in this program.
+    Internal,
+    /// Declared with `extern` and resolved when linking.
+    External,
 }
 
 #[derive(Debug)]
 pub struct FunctionDeclaration {
-    identifier: String,
-    parameters: Vec<Field>,
-    return_type: Option<Type>,
+    pub linkage: Linkage,
+    pub identifier: String,
+    pub parameters: Vec<Field>,
+    pub is_variadic: bool,
+    pub return_type: Option<Type>,
+    /// `None` for prototypes, which end in `;` rather than a body.
+    pub body: Option<Vec<BodyStatement>>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct StructUnionDeclaration {
-    is_union: bool,
-    identifier: String,
-    fields: Vec<Field>,
+    pub is_union: bool,
+    pub identifier: String,
+    /// `None` for opaque forward declarations such as `struct Foo;`, whose layout is unknown.
+    pub fields: Option<Vec<Field>>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct EnumDeclaration {
-    identifier: String,
-    variants: Vec<String>,
+    pub identifier: String,
+    /// `None` for opaque forward declarations such as `enum Foo;`.
+    pub variants: Option<Vec<EnumVariant>>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct EnumVariant {
+    pub identifier: String,
+    /// The explicit discriminant, if one was given.
+    pub value: Option<Expression>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct TypeDeclaration {
-    identifier: String,
-    ty: Type,
+    pub identifier: String,
+    pub ty: Type,
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -108,39 +215,267 @@ pub enum Statement {
     UnionDeclaration(StructUnionDeclaration),
     EnumDeclaration(EnumDeclaration),
     TypeDeclaration(TypeDeclaration),
+    /// Source that failed to parse and was skipped during error recovery.
+    Error(Span),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::ConstantDeclaration(decl) | Statement::VariableDeclaration(decl) => {
+                decl.span
+            }
+            Statement::FunctionDeclaration(decl) => decl.span,
+            Statement::StructDeclaration(decl) | Statement::UnionDeclaration(decl) => decl.span,
+            Statement::EnumDeclaration(decl) => decl.span,
+            Statement::TypeDeclaration(decl) => decl.span,
+            Statement::Error(span) => *span,
+        }
+    }
+}
+
+/// A statement inside a function body, as opposed to the top-level declarations of `Statement`.
+#[derive(Debug)]
+pub enum BodyStatement {
+    TypeDeclaration(TypeDeclaration),
+    ConstantDeclaration(ConstantVariableDeclaration),
+    VariableDeclaration(ConstantVariableDeclaration),
+    Assignment(AssignmentStatement),
+    If(IfStatement),
+    While(WhileLoop),
+    For(ForLoop),
+    Switch(SwitchStatement),
+    Break(Span),
+    Continue(Span),
+    Return(Option<Expression>, Span),
+    /// Source that failed to parse and was skipped during error recovery.
+    Error(Span),
+}
+
+impl BodyStatement {
+    pub fn span(&self) -> Span {
+        match self {
+            BodyStatement::TypeDeclaration(decl) => decl.span,
+            BodyStatement::ConstantDeclaration(decl) | BodyStatement::VariableDeclaration(decl) => {
+                decl.span
+            }
+            BodyStatement::Assignment(statement) => statement.span,
+            BodyStatement::If(statement) => statement.span,
+            BodyStatement::While(statement) => statement.span,
+            BodyStatement::For(statement) => statement.span,
+            BodyStatement::Switch(statement) => statement.span,
+            BodyStatement::Break(span)
+            | BodyStatement::Continue(span)
+            | BodyStatement::Return(_, span)
+            | BodyStatement::Error(span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssignmentOperator {
+    Assign,
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign,
+    BitwiseAndAssign,
+    BitwiseOrAssign,
+    BitwiseXorAssign,
+}
+
+#[derive(Debug)]
+pub struct AssignmentStatement {
+    pub target: Expression,
+    pub operator: AssignmentOperator,
+    pub value: Expression,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct IfStatement {
+    pub condition: Expression,
+    pub body: Vec<BodyStatement>,
+    pub else_body: Option<Vec<BodyStatement>>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct WhileLoop {
+    pub condition: Expression,
+    pub body: Vec<BodyStatement>,
+    pub span: Span,
 }
 
+/// `for i: u8 = start : end : step { ... }`, where the step is optional.
 #[derive(Debug)]
-pub enum Type {
+pub struct ForLoop {
+    pub variable: Field,
+    pub start: Expression,
+    pub end: Expression,
+    pub step: Option<Expression>,
+    pub body: Vec<BodyStatement>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct SwitchStatement {
+    pub value: Expression,
+    pub cases: Vec<CaseStatement>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct CaseStatement {
+    /// `None` for the `default` case.
+    pub value: Option<Expression>,
+    pub body: Vec<BodyStatement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+impl Type {
+    pub fn new(kind: TypeKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
     U8,
     I8,
     U16,
     I16,
+    Named(String),
+    Pointer(Box<Type>),
+    /// An array of the element type, sized by a constant expression.
+    Array(Box<Type>, Box<Expression>),
+    Function {
+        parameters: Vec<Type>,
+        is_variadic: bool,
+        return_type: Option<Box<Type>>,
+    },
 }
 
-#[derive(Debug)]
-pub enum Expression {
-    IntegerLiteral(IntegerLiteralKind, i16),
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    Plus,
+    Negate,
+    Not,
+    AddressOf,
+    Dereference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    ShiftLeft,
+    ShiftRight,
+    LogicalAnd,
+    LogicalOr,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanEqual,
+    GreaterThan,
+    GreaterThanEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind {
+    IntegerLiteral(IntegerLiteralKind, u32),
+    CharLiteral(u8),
+    StringLiteral(Vec<u8>),
+    Identifier(String),
+    SizeOf(Type),
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    Index {
+        base: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Member {
+        base: Box<Expression>,
+        member: String,
+    },
+    Cast {
+        ty: Type,
+        expression: Box<Expression>,
+    },
+    /// `[1, 2, 3]`
+    ArrayLiteral(Vec<Expression>),
+    /// `[value; count]`, an array of `count` copies of `value`.
+    ArrayRepeat {
+        value: Box<Expression>,
+        count: Box<Expression>,
+    },
+    /// `Point { x: 1, y: 2 }`
+    StructLiteral {
+        name: String,
+        fields: Vec<FieldInitializer>,
+    },
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    /// A bad lexeme in place of an operand. The lexer has already reported it, so the parser
+    /// carries on as if it were a valid operand.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInitializer {
+    pub identifier: String,
+    pub value: Expression,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct Field {
-    identifier: String,
-    field_type: Type,
+    pub identifier: String,
+    pub field_type: Type,
+    pub span: Span,
 }
 
 pub type ParseResult<T> = Result<(T, TokenPosition), ParseError>;
 
 #[derive(Clone, Debug)]
-pub struct TokenInput {
+pub struct TokenInput<'a> {
     // TODO: don't keep public
-    pub tokens: Vec<Token>, // TODO: don't keep public
+    pub tokens: Vec<Token<'a>>, // TODO: don't keep public
     index: usize,
     stack: Vec<usize>,
 }
 
-impl TokenInput {
-    pub fn new(tokens: Vec<Token>) -> Self {
+impl<'a> TokenInput<'a> {
+    pub fn new(tokens: Vec<Token<'a>>) -> Self {
         Self {
             tokens,
             index: 0,
@@ -165,43 +500,303 @@ impl TokenInput {
             .expect("Position stack is unexpectedly empty.");
     }
 
-    pub fn next(&mut self) -> Option<Token> {
-        if self.index >= self.tokens.len() {
+    /// Returns the next significant token. Raw trivia tokens, as produced by a `Lexer` without
+    /// `attach_trivia`, are skipped over; use `leading_trivia` to inspect them.
+    pub fn next(&mut self) -> Option<Token<'a>> {
+        let index = self.skip_trivia(self.index);
+
+        if index >= self.tokens.len() || self.tokens[index].kind == TokenKind::EOF {
             None
         } else {
-            let result = self.tokens[self.index].clone();
-            self.index += 1;
-            Some(result)
+            self.index = index + 1;
+            Some(self.tokens[index].clone())
         }
     }
 
-    pub fn current(self) -> Option<Token> {
-        if self.index >= self.tokens.len() {
+    pub fn current(&self) -> Option<Token<'a>> {
+        let index = self.skip_trivia(self.index);
+
+        if index >= self.tokens.len() || self.tokens[index].kind == TokenKind::EOF {
             None
         } else {
-            Some(self.tokens[self.index].clone())
+            Some(self.tokens[index].clone())
         }
     }
 
-    pub fn prev(self) -> Option<Token> {
-        if self.index <= 1 {
-            None
-        } else if (self.index - 1) >= self.tokens.len() {
-            None
-        } else {
-            Some(self.tokens[self.index - 1].clone())
+    /// The last significant token returned by `next`.
+    pub fn prev(&self) -> Option<Token<'a>> {
+        self.tokens[..self.index.min(self.tokens.len())]
+            .iter()
+            .rev()
+            .find(|token| !token.kind.is_trivia())
+            .cloned()
+    }
+
+    /// The whitespace and comments between the previous significant token and the next one,
+    /// including any trivia already attached to the next token. At the end of the input, this is
+    /// the trivia before `EOF`.
+    pub fn leading_trivia(&self) -> Vec<Trivia<'a>> {
+        let index = self.skip_trivia(self.index);
+
+        let raw = self.tokens[self.index.min(index)..index]
+            .iter()
+            .map(|token| Trivia {
+                kind: token.kind.clone(),
+                position: token.position.clone(),
+            });
+
+        let attached = self
+            .tokens
+            .get(index)
+            .into_iter()
+            .flat_map(|token| token.leading_trivia.iter().cloned());
+
+        raw.chain(attached).collect()
+    }
+
+    /// Consumes just the leading `>` of the next token when it is `>>`, `>=` or `>>=`, splitting it
+    /// in two so that the rest of it is left as the next token. Nothing valid can follow a cast's
+    /// closing `>` directly, but this way `<u8*>>p` is reported at the stray `>` rather than as a
+    /// cast missing its `>`. Returns `None`, consuming nothing, for any other token.
+    ///
+    /// The split is permanent, so it must not be backtracked over with `pop`.
+    pub fn split_greater_than(&mut self) -> Option<Token<'a>> {
+        let index = self.skip_trivia(self.index);
+        let token = self.tokens.get(index)?;
+
+        let rest = match token.kind {
+            TokenKind::RightChevron => TokenKind::GreaterThan,
+            TokenKind::GreaterThanEqual => TokenKind::Equal,
+            TokenKind::RightChevronEqual => TokenKind::GreaterThanEqual,
+            _ => return None,
+        };
+
+        let middle = TextPosition {
+            absolute: token.position.start.absolute + 1,
+            line: token.position.start.line,
+            column: token.position.start.column + 1,
+        };
+
+        let mut first = Token::new(
+            TokenKind::GreaterThan,
+            TokenPosition::new(token.position.start, middle),
+        );
+        first.leading_trivia = token.leading_trivia.clone();
+
+        let mut rest = Token::new(rest, TokenPosition::new(middle, token.position.end));
+        rest.trailing_trivia = token.trailing_trivia.clone();
+
+        self.tokens[index] = first;
+        self.tokens.insert(index + 1, rest);
+
+        self.next()
+    }
+
+    fn skip_trivia(&self, mut index: usize) -> usize {
+        while index < self.tokens.len() && self.tokens[index].kind.is_trivia() {
+            index += 1;
         }
+
+        index
     }
 }
 
-pub struct Parser {
-    pub input: TokenInput, // TODO: don't keep public
+fn starts_type(kind: &TokenKind<'_>) -> bool {
+    matches!(
+        kind,
+        TokenKind::Keyword(Keyword::U8 | Keyword::I8 | Keyword::U16 | Keyword::I16 | Keyword::Fn)
+            | TokenKind::Identifier(_)
+    )
+}
+
+/// Tokens that can start a unary expression, and so the right hand side of a binary operator.
+fn starts_operand(kind: &TokenKind<'_>) -> bool {
+    UnaryOperator::from_token(kind).is_some()
+        || matches!(
+            kind,
+            TokenKind::Identifier(_)
+                | TokenKind::IntegerLiteral(..)
+                | TokenKind::CharLiteral(_)
+                | TokenKind::StringLiteral(_)
+                | TokenKind::LeftParenthesis
+                | TokenKind::LeftBracket
+                | TokenKind::LessThan
+                | TokenKind::Keyword(Keyword::SizeOf)
+        )
+}
+
+/// Operand starts that could also follow a complete pointer type after `as`, as a binary operator
+/// or an array suffix, so that the `*` before them could mean either thing.
+fn is_ambiguous_after_pointer(kind: &TokenKind<'_>) -> bool {
+    BinaryOperator::from_token(kind).is_some() || *kind == TokenKind::LeftBracket
+}
+
+/// Keywords that can only start a top-level declaration, where error recovery resumes parsing.
+const DECLARATION_KEYWORDS: [Keyword; 8] = [
+    Keyword::Fn,
+    Keyword::Extern,
+    Keyword::Struct,
+    Keyword::Union,
+    Keyword::Enum,
+    Keyword::Const,
+    Keyword::Var,
+    Keyword::Type,
+];
+
+/// Keywords that start a statement or a case inside a function body.
+const STATEMENT_KEYWORDS: [Keyword; 12] = [
+    Keyword::Const,
+    Keyword::Var,
+    Keyword::Type,
+    Keyword::If,
+    Keyword::While,
+    Keyword::For,
+    Keyword::Switch,
+    Keyword::Case,
+    Keyword::Default,
+    Keyword::Break,
+    Keyword::Continue,
+    Keyword::Return,
+];
+
+pub struct Parser<'a> {
+    pub input: TokenInput<'a>, // TODO: don't keep public
+    file: FileId,
+    errors: Vec<ParseError>,
+    // Everything tried at the furthest token index that parsing has failed at so far.
+    expected: Vec<Expected>,
+    expected_index: usize,
+    // Cleared while parsing an expression that a block follows, see `parse_condition`.
+    struct_literals: bool,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(file: FileId, tokens: Vec<Token<'a>>) -> Self {
         Self {
             input: TokenInput::new(tokens),
+            file,
+            errors: Vec::new(),
+            expected: Vec::new(),
+            expected_index: 0,
+            struct_literals: true,
+        }
+    }
+
+    /// Notes that `expected` would have been accepted at the current token. Expectations from
+    /// every alternative tried at the same token accumulate, and are reported together by
+    /// `ParseError::unexpected` if none of them match.
+    fn expect(&mut self, expected: Expected) {
+        let index = self.input.skip_trivia(self.input.index);
+
+        if index > self.expected_index {
+            self.expected.clear();
+            self.expected_index = index;
+        }
+
+        if index == self.expected_index && !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+
+    /// Returns the errors recorded by the recovering parse functions so far.
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Parses a declaration, or on failure records the error, skips to the next likely start of a
+    /// declaration and returns a `Statement::Error` covering the skipped source.
+    pub fn parse_declaration_recovering(&mut self) -> Statement {
+        let start = self.input.index;
+
+        match self.parse_declaration() {
+            ParseResult::Ok((statement, _)) => statement,
+            ParseResult::Err(err) => {
+                Statement::Error(self.recover(err, start, &DECLARATION_KEYWORDS))
+            }
+        }
+    }
+
+    /// Like `parse_declaration_recovering`, but for statements in a function body. Running out of
+    /// input is still an error, since the enclosing block can't be closed.
+    fn parse_statement_recovering(&mut self) -> ParseResult<BodyStatement> {
+        let start = self.input.index;
+
+        match self.parse_statement() {
+            ParseResult::Ok(result) => ParseResult::Ok(result),
+            ParseResult::Err(err) => match err {
+                ParseError {
+                    kind: ParseErrorKind::UnexpectedEOF,
+                    msg: _,
+                    span: _,
+                    expected: _,
+                } => ParseResult::Err(err),
+                _ => {
+                    let span = self.recover(err, start, &STATEMENT_KEYWORDS);
+                    let position = self.input.prev().unwrap_or_default().position;
+
+                    ParseResult::Ok((BodyStatement::Error(span), position))
+                }
+            },
+        }
+    }
+
+    /// Panic mode recovery: records `err`, then skips tokens until just after a `;`, or just before
+    /// a `}` or one of `keywords`. Braces opened while skipping are skipped up to their matching
+    /// `}`, so that a broken `if x {` doesn't close the enclosing block early. The same goes for
+    /// braces opened before the error, such as the body of a broken struct. Returns the span of
+    /// everything from `start` that was skipped.
+    fn recover(&mut self, err: ParseError, start: usize, keywords: &[Keyword]) -> Span {
+        self.errors.push(err);
+
+        let mut depth = self.input.tokens[start..self.input.index.min(self.input.tokens.len())]
+            .iter()
+            .fold(0, |depth: usize, token| match token.kind {
+                TokenKind::LeftBrace => depth + 1,
+                TokenKind::RightBrace => depth.saturating_sub(1),
+                _ => depth,
+            });
+
+        // Always skip at least one token, or an error at a synchronization point would repeat
+        // forever.
+        if self.input.index == start {
+            if let Some(Token {
+                kind: TokenKind::LeftBrace,
+                ..
+            }) = self.input.next()
+            {
+                depth += 1;
+            }
+        }
+
+        loop {
+            match self.input.current() {
+                None => break,
+                Some(token) => match token.kind {
+                    TokenKind::LeftBrace => depth += 1,
+                    TokenKind::RightBrace if depth > 0 => depth -= 1,
+                    TokenKind::RightBrace => break,
+                    TokenKind::SemiColon if depth == 0 => {
+                        self.input.next();
+                        break;
+                    }
+                    TokenKind::Keyword(keyword) if depth == 0 && keywords.contains(&keyword) => {
+                        break
+                    }
+                    _ => (),
+                },
+            }
+
+            self.input.next();
+        }
+
+        let first = match self.input.tokens.get(self.input.skip_trivia(start)) {
+            Some(token) => self.span(&token.position),
+            None => Span::new(self.file, 0, 0),
+        };
+
+        match self.input.prev() {
+            Some(last) => first.to(self.span(&last.position)),
+            None => first,
         }
     }
 
@@ -210,21 +805,47 @@ impl Parser {
         let next = self.input.next();
         self.input.pop();
 
-        match next {
-            Option::None => false,
-            Option::Some(_) => true,
+        next.is_none()
+    }
+
+    fn span(&self, position: &TokenPosition) -> Span {
+        position.to_span(self.file)
+    }
+
+    /// The span of the next significant token, where the node about to be parsed starts.
+    fn current_span(&self) -> Span {
+        match self
+            .input
+            .tokens
+            .get(self.input.skip_trivia(self.input.index))
+        {
+            Some(token) => self.span(&token.position),
+            None => Span::new(self.file, 0, 0),
+        }
+    }
+
+    /// The span from `start` up to and including the last token consumed.
+    fn span_from(&self, start: Span) -> Span {
+        match self.input.prev() {
+            Some(token) => start.to(self.span(&token.position)),
+            None => start,
         }
     }
 
     pub fn parse_declaration(&mut self) -> ParseResult<Statement> {
+        // Once an alternative has consumed a token it is committed to, and its error is the one
+        // worth reporting; the remaining alternatives are only tried if it failed immediately.
+        let start = self.input.index;
+
         match self.parse_const_or_var_decl(false) {
             ParseResult::Err(err) => match err {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -237,9 +858,10 @@ impl Parser {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -252,9 +874,10 @@ impl Parser {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -267,9 +890,10 @@ impl Parser {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -282,9 +906,10 @@ impl Parser {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -292,14 +917,31 @@ impl Parser {
             }
         }
 
+        match self.parse_enum_decl() {
+            ParseResult::Err(err) => match err {
+                ParseError {
+                    kind: ParseErrorKind::UnexpectedEOF,
+                    msg: _,
+                    span: _,
+                    expected: _,
+                } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
+                _ => (),
+            },
+            ParseResult::Ok((decl, pos)) => {
+                return ParseResult::Ok((Statement::EnumDeclaration(decl), pos))
+            }
+        }
+
         match self.parse_type_decl() {
             ParseResult::Err(err) => match err {
                 ParseError {
                     kind: ParseErrorKind::UnexpectedEOF,
                     msg: _,
-                    line: _,
-                    column: _,
+                    span: _,
+                    expected: _,
                 } => return ParseResult::Err(err),
+                _ if self.input.index != start => return ParseResult::Err(err),
                 _ => (),
             },
             ParseResult::Ok((decl, pos)) => {
@@ -307,15 +949,18 @@ impl Parser {
             }
         }
 
-        ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedToken))
+        ParseResult::Err(ParseError::unexpected(self))
     }
 
     fn parse_const_or_var_decl(
         &mut self,
         is_var: bool,
     ) -> ParseResult<ConstantVariableDeclaration> {
+        let start = self.current_span();
+
         // "const" / "var" Identifier ":" Type "=" Expression ";"
-        // keyword Field "=" Expression ";"
+        // "var" Identifier ":" Type ";"
+        // keyword Field ( "=" Expression )? ";"
 
         let keyword = if is_var { Keyword::Var } else { Keyword::Const };
 
@@ -331,16 +976,39 @@ impl Parser {
             ParseResult::Ok((field, _)) => field,
         };
 
-        // "="
-        match self.parse_token(TokenKind::Equal) {
-            ParseResult::Err(err) => return ParseResult::Err(err),
-            ParseResult::Ok(_) => (),
-        }
+        // ( "=" Expression )?
+        let expression = match self.input.current() {
+            Some(Token {
+                kind: TokenKind::SemiColon,
+                ..
+            }) if is_var => None,
+            // Leave the `;` for error recovery to stop at.
+            Some(Token {
+                kind: TokenKind::SemiColon,
+                ..
+            }) => {
+                return ParseResult::Err(ParseError::uninitialized_constant(
+                    &field.identifier,
+                    self.span_from(start),
+                ))
+            }
+            _ => {
+                if is_var {
+                    self.expect(Expected::Token(TokenKind::SemiColon));
+                }
 
-        // Expression
-        let expression = match self.parse_expression() {
-            ParseResult::Err(err) => return ParseResult::Err(err),
-            ParseResult::Ok((expression, _)) => expression,
+                // "="
+                match self.parse_token(TokenKind::Equal) {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok(_) => (),
+                }
+
+                // Expression
+                match self.parse_expression() {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((expression, _)) => Some(expression),
+                }
+            }
         };
 
         // ";"
@@ -355,144 +1023,1502 @@ impl Parser {
                 identifier: field.identifier,
                 const_type: field.field_type,
                 value: expression,
+                span: self.span_from(start),
             },
             position,
         ))
     }
 
     fn parse_fn_decl(&mut self) -> ParseResult<FunctionDeclaration> {
-        ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedToken))
-    }
+        let start = self.current_span();
 
-    fn parse_struct_or_union_decl(
-        &mut self,
-        is_union: bool,
-    ) -> ParseResult<StructUnionDeclaration> {
-        // "struct" / "union" Identifier "{" ( Field ";" )* "}"
-        // keyword Identifir "{" ( Field ";" )* "}"
+        // "extern"? "fn" Identifier "(" Parameters ")" ( ":" Type )? ( ";" | Block )
 
-        let keyword = if is_union {
-            Keyword::Union
-        } else {
-            Keyword::Struct
+        // "extern"?
+        let linkage = match self.parse_keyword(Keyword::Extern) {
+            ParseResult::Err(_) => Linkage::Internal,
+            ParseResult::Ok(_) => Linkage::External,
         };
 
-        let mut fields = Vec::new();
-
-        // keyword Identifier
-        let identifier = match self.parse_keyword_with_identifier(keyword) {
-            ParseResult::Err(error) => return ParseResult::Err(error),
+        // "fn" Identifier
+        let identifier = match self.parse_keyword_with_identifier(Keyword::Fn) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
             ParseResult::Ok((identifier, _)) => identifier,
         };
 
-        // "{"
-        match self.parse_token(TokenKind::LeftBrace) {
+        // "("
+        match self.parse_token(TokenKind::LeftParenthesis) {
             ParseResult::Err(err) => return ParseResult::Err(err),
             ParseResult::Ok(_) => (),
         }
 
-        // ( Field "," )*
+        // Parameters ")"
+        let (parameters, is_variadic) = match self.parse_parameters() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((parameters, _)) => parameters,
+        };
+
+        // ( ":" Type )?
+        let return_type = match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(_) => None,
+            ParseResult::Ok(_) => match self.parse_type() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((ty, _)) => Some(ty),
+            },
+        };
+
+        // ";"
+        // External functions are only ever declared, never defined.
+        let (body, position) = match self.parse_token(TokenKind::SemiColon) {
+            ParseResult::Ok((_, position)) => (None, position),
+            ParseResult::Err(err) if linkage == Linkage::External => return ParseResult::Err(err),
+            // Block
+            ParseResult::Err(_) => match self.parse_block() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((body, position)) => (Some(body), position),
+            },
+        };
+
+        ParseResult::Ok((
+            FunctionDeclaration {
+                linkage,
+                identifier,
+                parameters,
+                is_variadic,
+                return_type,
+                body,
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    /// Parses a parameter list up to and including its closing `)`, returning the parameters and
+    /// whether they end in the variadic marker `...`, which must follow at least one parameter.
+    fn parse_parameters(&mut self) -> ParseResult<(Vec<Field>, bool)> {
+        // ( Field "," )* ")"
+        // ( Field "," )+ "..." ")"
+
+        let mut parameters = Vec::new();
+
         loop {
-            // Parse fields until there are no more to parse.
-            // parse_field will return ParseResult::Err when it is unable to parse another field:
-            //
-            match self.parse_field() {
-                ParseResult::Err(err) => match err {
-                    ParseError {
-                        kind: ParseErrorKind::UnexpectedEOF,
-                        msg: _,
-                        line: _,
-                        column: _,
-                    } => {
-                        return ParseResult::Err(ParseError::from_kind(
-                            self,
-                            ParseErrorKind::UnexpectedEOF,
-                        ))
+            // ")"
+            if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::RightParenthesis) {
+                return ParseResult::Ok(((parameters, false), position));
+            }
+
+            // "..." ")"
+            if !parameters.is_empty() && self.parse_token(TokenKind::Ellipsis).is_ok() {
+                return match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(err) => ParseResult::Err(err),
+                    ParseResult::Ok((_, position)) => {
+                        ParseResult::Ok(((parameters, true), position))
                     }
-                    _ => break,
-                },
-                ParseResult::Ok((field, _)) => fields.push(field),
+                };
             }
 
-            match self.parse_token(TokenKind::Comma) {
-                ParseResult::Err(err) => {
-                    return ParseResult::Err(ParseError::unexpected_token(self))
-                }
-                ParseResult::Ok(_) => (),
+            // Field
+            match self.parse_field() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((field, _)) => parameters.push(field),
             }
-        }
 
-        // "}"
-        match self.parse_token(TokenKind::RightBrace) {
-            ParseResult::Err(err) => ParseResult::Err(err),
-            ParseResult::Ok((_, remaining)) => ParseResult::Ok((
-                StructUnionDeclaration {
-                    is_union,
-                    identifier,
-                    fields,
-                },
-                remaining,
-            )),
+            // ","
+            // The comma after the last parameter is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                return match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(_) => ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, position)) => {
+                        ParseResult::Ok(((parameters, false), position))
+                    }
+                };
+            }
         }
     }
 
-    fn parse_type_decl(&mut self) -> ParseResult<TypeDeclaration> {
-        // "type" Identifier "=" Type ";"
-        // keyword Identifir "=" Type ";"
+    fn parse_block(&mut self) -> ParseResult<Vec<BodyStatement>> {
+        // "{" Statement* "}"
 
-        // keyword Identifier
-        let identifier = match self.parse_keyword_with_identifier(Keyword::Type) {
-            ParseResult::Err(error) => return ParseResult::Err(error),
-            ParseResult::Ok((identifier, _)) => identifier,
-        };
+        // "{"
+        match self.parse_token(TokenKind::LeftBrace) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let mut statements = Vec::new();
+
+        loop {
+            // "}"
+            if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::RightBrace) {
+                return ParseResult::Ok((statements, position));
+            }
+
+            // Statement
+            match self.parse_statement_recovering() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((statement, _)) => statements.push(statement),
+            }
+        }
+    }
+
+    pub fn parse_statement(&mut self) -> ParseResult<BodyStatement> {
+        let start = self.current_span();
+
+        let keyword = match self.input.current() {
+            None => {
+                self.expect(Expected::Production("statement"));
+                return ParseResult::Err(ParseError::unexpected(self));
+            }
+            Some(Token {
+                kind: TokenKind::Keyword(keyword),
+                ..
+            }) => keyword,
+            // Anything that doesn't start with a keyword has to be an assignment.
+            Some(_) => {
+                return match self.parse_assignment() {
+                    ParseResult::Err(err) => ParseResult::Err(err),
+                    ParseResult::Ok((assignment, position)) => {
+                        ParseResult::Ok((BodyStatement::Assignment(assignment), position))
+                    }
+                }
+            }
+        };
+
+        match keyword {
+            Keyword::Const => match self.parse_const_or_var_decl(false) {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((decl, position)) => {
+                    ParseResult::Ok((BodyStatement::ConstantDeclaration(decl), position))
+                }
+            },
+            Keyword::Var => match self.parse_const_or_var_decl(true) {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((decl, position)) => {
+                    ParseResult::Ok((BodyStatement::VariableDeclaration(decl), position))
+                }
+            },
+            Keyword::Type => match self.parse_type_decl() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((decl, position)) => {
+                    ParseResult::Ok((BodyStatement::TypeDeclaration(decl), position))
+                }
+            },
+            Keyword::If => match self.parse_if() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((statement, position)) => {
+                    ParseResult::Ok((BodyStatement::If(statement), position))
+                }
+            },
+            Keyword::While => match self.parse_while() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((statement, position)) => {
+                    ParseResult::Ok((BodyStatement::While(statement), position))
+                }
+            },
+            Keyword::For => match self.parse_for() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((statement, position)) => {
+                    ParseResult::Ok((BodyStatement::For(statement), position))
+                }
+            },
+            Keyword::Switch => match self.parse_switch() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((statement, position)) => {
+                    ParseResult::Ok((BodyStatement::Switch(statement), position))
+                }
+            },
+            Keyword::Break => match self.parse_keyword_with_semicolon(Keyword::Break) {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((_, position)) => {
+                    ParseResult::Ok((BodyStatement::Break(self.span_from(start)), position))
+                }
+            },
+            Keyword::Continue => match self.parse_keyword_with_semicolon(Keyword::Continue) {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((_, position)) => {
+                    ParseResult::Ok((BodyStatement::Continue(self.span_from(start)), position))
+                }
+            },
+            Keyword::Return => match self.parse_return() {
+                ParseResult::Err(err) => ParseResult::Err(err),
+                ParseResult::Ok((value, position)) => ParseResult::Ok((
+                    BodyStatement::Return(value, self.span_from(start)),
+                    position,
+                )),
+            },
+            _ => {
+                self.expect(Expected::Production("statement"));
+                ParseResult::Err(ParseError::unexpected(self))
+            }
+        }
+    }
+
+    fn parse_assignment(&mut self) -> ParseResult<AssignmentStatement> {
+        let start = self.current_span();
+
+        // Expression AssignmentOperator Expression ";"
+
+        // Expression
+        let target = match self.parse_expression() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((target, _)) => target,
+        };
+
+        // AssignmentOperator
+        let operator = match self
+            .input
+            .current()
+            .and_then(|token| AssignmentOperator::from_token(&token.kind))
+        {
+            None => {
+                self.expect(Expected::Production("assignment operator"));
+                return ParseResult::Err(ParseError::unexpected(self));
+            }
+            Some(operator) => operator,
+        };
+
+        self.input.next();
+
+        // Expression
+        let value = match self.parse_expression() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((value, _)) => value,
+        };
+
+        // ";"
+        match self.parse_token(TokenKind::SemiColon) {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((_, position)) => ParseResult::Ok((
+                AssignmentStatement {
+                    target,
+                    operator,
+                    value,
+                    span: self.span_from(start),
+                },
+                position,
+            )),
+        }
+    }
+
+    fn parse_if(&mut self) -> ParseResult<IfStatement> {
+        let start = self.current_span();
+
+        // "if" Expression Block ( "else" Block )?
+
+        // "if"
+        match self.parse_keyword(Keyword::If) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        let condition = match self.parse_condition() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((condition, _)) => condition,
+        };
+
+        // Block
+        let (body, position) = self.parse_block()?;
+
+        // ( "else" Block )?
+        let (else_body, position) = match self.parse_keyword(Keyword::Else) {
+            ParseResult::Err(_) => (None, position),
+            ParseResult::Ok(_) => match self.parse_block() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((else_body, position)) => (Some(else_body), position),
+            },
+        };
+
+        ParseResult::Ok((
+            IfStatement {
+                condition,
+                body,
+                else_body,
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    fn parse_while(&mut self) -> ParseResult<WhileLoop> {
+        let start = self.current_span();
+
+        // "while" Expression Block
+
+        // "while"
+        match self.parse_keyword(Keyword::While) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        let condition = match self.parse_condition() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((condition, _)) => condition,
+        };
+
+        // Block
+        match self.parse_block() {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((body, position)) => ParseResult::Ok((
+                WhileLoop {
+                    condition,
+                    body,
+                    span: self.span_from(start),
+                },
+                position,
+            )),
+        }
+    }
+
+    fn parse_for(&mut self) -> ParseResult<ForLoop> {
+        let for_start = self.current_span();
+
+        // "for" Field "=" Expression ":" Expression ( ":" Expression )? Block
+
+        // "for"
+        match self.parse_keyword(Keyword::For) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Identifier ":" Type
+        let variable = match self.parse_field() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((variable, _)) => variable,
+        };
+
+        // "="
+        match self.parse_token(TokenKind::Equal) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        let start = match self.parse_expression() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((start, _)) => start,
+        };
+
+        // ":" Expression
+        match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let end = match self.parse_condition() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((end, _)) => end,
+        };
+
+        // ( ":" Expression )?
+        let step = match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(_) => None,
+            ParseResult::Ok(_) => match self.parse_condition() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((step, _)) => Some(step),
+            },
+        };
+
+        // Block
+        match self.parse_block() {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((body, position)) => ParseResult::Ok((
+                ForLoop {
+                    variable,
+                    start,
+                    end,
+                    step,
+                    body,
+                    span: self.span_from(for_start),
+                },
+                position,
+            )),
+        }
+    }
+
+    fn parse_switch(&mut self) -> ParseResult<SwitchStatement> {
+        let start = self.current_span();
+
+        // "switch" Expression "{" CaseStatement* "}"
+
+        // "switch"
+        match self.parse_keyword(Keyword::Switch) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        let value = match self.parse_condition() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((value, _)) => value,
+        };
+
+        // "{"
+        match self.parse_token(TokenKind::LeftBrace) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let mut cases = Vec::new();
+
+        loop {
+            // "}"
+            if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::RightBrace) {
+                return ParseResult::Ok((
+                    SwitchStatement {
+                        value,
+                        cases,
+                        span: self.span_from(start),
+                    },
+                    position,
+                ));
+            }
+
+            // CaseStatement
+            match self.parse_case() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((case, _)) => cases.push(case),
+            }
+        }
+    }
+
+    fn parse_case(&mut self) -> ParseResult<CaseStatement> {
+        let start = self.current_span();
+
+        // "case" Expression ":" Statement*
+        // "default" ":" Statement*
+
+        // "case" Expression / "default"
+        let value = match self.parse_keyword(Keyword::Default) {
+            ParseResult::Ok(_) => None,
+            ParseResult::Err(_) => match self.parse_keyword(Keyword::Case) {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok(_) => match self.parse_expression() {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((value, _)) => Some(value),
+                },
+            },
+        };
+
+        // ":"
+        let mut position = match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((_, position)) => position,
+        };
+
+        let mut body = Vec::new();
+
+        // Statement*
+        // The body runs until the next case or the end of the switch.
+        loop {
+            match self.input.current() {
+                Some(Token {
+                    kind:
+                        TokenKind::Keyword(Keyword::Case)
+                        | TokenKind::Keyword(Keyword::Default)
+                        | TokenKind::RightBrace,
+                    ..
+                })
+                | None => break,
+                Some(_) => (),
+            }
+
+            match self.parse_statement_recovering() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((statement, end)) => {
+                    body.push(statement);
+                    position = end;
+                }
+            }
+        }
+
+        ParseResult::Ok((
+            CaseStatement {
+                value,
+                body,
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    fn parse_return(&mut self) -> ParseResult<Option<Expression>> {
+        // "return" Expression? ";"
+
+        // "return"
+        match self.parse_keyword(Keyword::Return) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // ";"
+        if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::SemiColon) {
+            return ParseResult::Ok((None, position));
+        }
+
+        // Expression
+        let value = match self.parse_expression() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((value, _)) => value,
+        };
+
+        // ";"
+        match self.parse_token(TokenKind::SemiColon) {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((_, position)) => ParseResult::Ok((Some(value), position)),
+        }
+    }
+
+    fn parse_keyword_with_semicolon(&mut self, target: Keyword) -> ParseResult<()> {
+        match self.parse_keyword(target) {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok(_) => self.parse_token(TokenKind::SemiColon),
+        }
+    }
+
+    fn parse_struct_or_union_decl(
+        &mut self,
+        is_union: bool,
+    ) -> ParseResult<StructUnionDeclaration> {
+        let start = self.current_span();
+
+        // "struct" / "union" Identifier ";"
+        // "struct" / "union" Identifier "{" ( Field "," )* "}"
+        // keyword Identifir "{" ( Field "," )* "}"
+
+        let keyword = if is_union {
+            Keyword::Union
+        } else {
+            Keyword::Struct
+        };
+
+        let mut fields = Vec::new();
+
+        // keyword Identifier
+        let identifier = match self.parse_keyword_with_identifier(keyword) {
+            ParseResult::Err(error) => return ParseResult::Err(error),
+            ParseResult::Ok((identifier, _)) => identifier,
+        };
+
+        // ";"
+        if let ParseResult::Ok((_, remaining)) = self.parse_token(TokenKind::SemiColon) {
+            return ParseResult::Ok((
+                StructUnionDeclaration {
+                    is_union,
+                    identifier,
+                    fields: None,
+                    span: self.span_from(start),
+                },
+                remaining,
+            ));
+        }
+
+        // "{"
+        match self.parse_token(TokenKind::LeftBrace) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // ( Field "," )* "}"
+        let remaining = loop {
+            // "}"
+            if let ParseResult::Ok((_, remaining)) = self.parse_token(TokenKind::RightBrace) {
+                break remaining;
+            }
+
+            // Field
+            match self.parse_field() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((field, _)) => fields.push(field),
+            }
+
+            // ","
+            // The comma after the last field is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                match self.parse_token(TokenKind::RightBrace) {
+                    ParseResult::Err(_) => return ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, remaining)) => break remaining,
+                }
+            }
+        };
+
+        ParseResult::Ok((
+            StructUnionDeclaration {
+                is_union,
+                identifier,
+                fields: Some(fields),
+                span: self.span_from(start),
+            },
+            remaining,
+        ))
+    }
+
+    fn parse_enum_decl(&mut self) -> ParseResult<EnumDeclaration> {
+        let start = self.current_span();
+
+        // "enum" Identifier ";"
+        // "enum" Identifier "{" ( ( Identifier | Identifier "=" Expression ) "," )* "}"
+
+        // "enum" Identifier
+        let identifier = match self.parse_keyword_with_identifier(Keyword::Enum) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((identifier, _)) => identifier,
+        };
+
+        // ";"
+        if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::SemiColon) {
+            return ParseResult::Ok((
+                EnumDeclaration {
+                    identifier,
+                    variants: None,
+                    span: self.span_from(start),
+                },
+                position,
+            ));
+        }
+
+        // "{"
+        match self.parse_token(TokenKind::LeftBrace) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let mut variants = Vec::new();
+
+        let position = loop {
+            // "}"
+            if let ParseResult::Ok((_, position)) = self.parse_token(TokenKind::RightBrace) {
+                break position;
+            }
+
+            match self.parse_enum_variant() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((variant, _)) => variants.push(variant),
+            }
+
+            // ","
+            // The comma after the last variant is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                match self.parse_token(TokenKind::RightBrace) {
+                    ParseResult::Err(_) => return ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, position)) => break position,
+                }
+            }
+        };
+
+        ParseResult::Ok((
+            EnumDeclaration {
+                identifier,
+                variants: Some(variants),
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    fn parse_enum_variant(&mut self) -> ParseResult<EnumVariant> {
+        let start = self.current_span();
+
+        // Identifier ( "=" Expression )?
+
+        // Identifier
+        let (identifier, mut position) = self.parse_identifier()?;
+
+        // ( "=" Expression )?
+        let value = match self.parse_token(TokenKind::Equal) {
+            ParseResult::Err(_) => None,
+            ParseResult::Ok(_) => match self.parse_expression() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((value, end)) => {
+                    position = end;
+                    Some(value)
+                }
+            },
+        };
+
+        ParseResult::Ok((
+            EnumVariant {
+                identifier,
+                value,
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    fn parse_type_decl(&mut self) -> ParseResult<TypeDeclaration> {
+        let start = self.current_span();
+
+        // "type" Identifier "=" Type ";"
+        // keyword Identifir "=" Type ";"
+
+        // keyword Identifier
+        let identifier = match self.parse_keyword_with_identifier(Keyword::Type) {
+            ParseResult::Err(error) => return ParseResult::Err(error),
+            ParseResult::Ok((identifier, _)) => identifier,
+        };
+
+        // "="
+        match self.parse_token(TokenKind::Equal) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Type
+        let ty = match self.parse_type() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((ty, _)) => ty,
+        };
+
+        // ";"
+        let position = match self.parse_token(TokenKind::SemiColon) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((_, remaining)) => remaining,
+        };
+
+        ParseResult::Ok((
+            TypeDeclaration {
+                identifier,
+                ty,
+                span: self.span_from(start),
+            },
+            position,
+        ))
+    }
+
+    pub fn parse_expression(&mut self) -> ParseResult<Expression> {
+        self.parse_binary_expression(1)
+    }
+
+    /// Parses an expression that is directly followed by a block, such as the condition of an
+    /// `if`. Struct literals aren't allowed outside of brackets here, or `if x { ... }` would parse
+    /// `x { ... }` as one.
+    fn parse_condition(&mut self) -> ParseResult<Expression> {
+        let allowed = std::mem::replace(&mut self.struct_literals, false);
+        let result = self.parse_expression();
+        self.struct_literals = allowed;
+
+        result
+    }
+
+    /// Parses an expression enclosed in brackets of some kind, where struct literals are always
+    /// unambiguous.
+    fn parse_nested_expression(&mut self) -> ParseResult<Expression> {
+        let allowed = std::mem::replace(&mut self.struct_literals, true);
+        let result = self.parse_expression();
+        self.struct_literals = allowed;
 
-        // "="
-        match self.parse_token(TokenKind::Equal) {
-            ParseResult::Err(err) => return ParseResult::Err(err),
-            ParseResult::Ok(_) => (),
+        result
+    }
+
+    /// Precedence climbing: parses a unary expression, then folds in every following binary
+    /// operator that binds at least as tightly as `min_precedence`. Precedences and associativity
+    /// come from the operator table in `operators`.
+    fn parse_binary_expression(&mut self, min_precedence: u8) -> ParseResult<Expression> {
+        let (mut lhs, mut position) = self.parse_as_expression()?;
+
+        loop {
+            let operator = match self
+                .input
+                .current()
+                .and_then(|token| BinaryOperator::from_token(&token.kind))
+            {
+                Some(operator) if operator.precedence() >= min_precedence => operator,
+                _ => break,
+            };
+
+            self.input.next();
+
+            // A left associative operator's right hand side may only contain operators that bind
+            // strictly tighter, while a right associative one also takes operators of its own
+            // precedence.
+            let rhs_precedence = match operator.associativity() {
+                Associativity::Left => operator.precedence() + 1,
+                Associativity::Right => operator.precedence(),
+            };
+
+            let (rhs, rhs_position) = self.parse_binary_expression(rhs_precedence)?;
+
+            let span = lhs.span.to(rhs.span);
+            lhs = Expression::new(
+                ExpressionKind::Binary(operator, Box::new(lhs), Box::new(rhs)),
+                span,
+            );
+            position = rhs_position;
+        }
+
+        ParseResult::Ok((lhs, position))
+    }
+
+    /// Parses a unary expression followed by any number of `as Type` casts, which bind looser than
+    /// unary operators but tighter than every binary operator.
+    fn parse_as_expression(&mut self) -> ParseResult<Expression> {
+        let (mut expression, mut position) = self.parse_unary_expression()?;
+
+        // ( "as" Type )*
+        while let Some(Token {
+            kind: TokenKind::Keyword(Keyword::As),
+            ..
+        }) = self.input.current()
+        {
+            self.input.next();
+
+            let (ty, end) = self.parse_postfix_type(true)?;
+
+            let span = expression.span.to(ty.span);
+            expression = Expression::new(
+                ExpressionKind::Cast {
+                    ty,
+                    expression: Box::new(expression),
+                },
+                span,
+            );
+            position = end;
+        }
+
+        ParseResult::Ok((expression, position))
+    }
+
+    fn parse_unary_expression(&mut self) -> ParseResult<Expression> {
+        let start = match self.input.current() {
+            None => {
+                self.expect(Expected::Production("expression"));
+                return ParseResult::Err(ParseError::unexpected(self));
+            }
+            Some(token) => token,
+        };
+
+        let operator = match UnaryOperator::from_token(&start.kind) {
+            Some(operator) => operator,
+            None if start.kind == TokenKind::LessThan => return self.parse_cast_expression(),
+            None => return self.parse_postfix_expression(),
+        };
+
+        self.input.next();
+
+        match self.parse_unary_expression() {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((operand, position)) => {
+                let span = self.span(&start.position).to(operand.span);
+
+                ParseResult::Ok((
+                    Expression::new(ExpressionKind::Unary(operator, Box::new(operand)), span),
+                    position,
+                ))
+            }
+        }
+    }
+
+    fn parse_cast_expression(&mut self) -> ParseResult<Expression> {
+        // "<" Type ">" Expression
+
+        self.input.push();
+
+        // "<"
+        let start = match self.parse_token(TokenKind::LessThan) {
+            ParseResult::Err(err) => {
+                self.input.drop();
+                return ParseResult::Err(err);
+            }
+            ParseResult::Ok((_, position)) => position,
+        };
+
+        // A "<" that isn't followed by a type is a comparison missing its left hand side rather
+        // than a cast, so report it as such.
+        if !self
+            .input
+            .current()
+            .is_some_and(|token| starts_type(&token.kind))
+        {
+            self.input.pop();
+            self.expect(Expected::Production("expression"));
+            return ParseResult::Err(ParseError::unexpected(self));
         }
 
+        self.input.drop();
+
         // Type
         let ty = match self.parse_type() {
             ParseResult::Err(err) => return ParseResult::Err(err),
             ParseResult::Ok((ty, _)) => ty,
         };
 
-        // ";"
-        let position = match self.parse_token(TokenKind::SemiColon) {
+        // ">"
+        match self.parse_closing_chevron() {
             ParseResult::Err(err) => return ParseResult::Err(err),
-            ParseResult::Ok((_, remaining)) => remaining,
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        match self.parse_unary_expression() {
+            ParseResult::Err(err) => ParseResult::Err(err),
+            ParseResult::Ok((expression, position)) => {
+                let span = self.span(&start).to(expression.span);
+
+                ParseResult::Ok((
+                    Expression::new(
+                        ExpressionKind::Cast {
+                            ty,
+                            expression: Box::new(expression),
+                        },
+                        span,
+                    ),
+                    position,
+                ))
+            }
+        }
+    }
+
+    /// Parses the `>` closing a cast. The lexer reads `<u8>>=` and `<u8*>>p` as ending in a `>>=`
+    /// or `>>` token, which is split so that only its first `>` is consumed.
+    fn parse_closing_chevron(&mut self) -> ParseResult<()> {
+        let index = self.input.skip_trivia(self.input.index);
+
+        match self.input.split_greater_than() {
+            Some(token) => {
+                // Any expectations recorded past the split token have moved along by one.
+                if self.expected_index > index {
+                    self.expected_index += 1;
+                }
+
+                ParseResult::Ok(((), token.position))
+            }
+            None => self.parse_token(TokenKind::GreaterThan),
+        }
+    }
+
+    fn parse_postfix_expression(&mut self) -> ParseResult<Expression> {
+        let (mut expression, mut position) = self.parse_primary_expression()?;
+        let start = expression.span;
+
+        loop {
+            let kind = match self.input.current() {
+                None => break,
+                Some(token) => token.kind,
+            };
+
+            let (kind, end) = match kind {
+                // Expression "(" ( Expression "," )* ")"
+                TokenKind::LeftParenthesis => {
+                    self.input.next();
+
+                    let (arguments, end) = self.parse_arguments()?;
+
+                    (
+                        ExpressionKind::Call {
+                            callee: Box::new(expression),
+                            arguments,
+                        },
+                        end,
+                    )
+                }
+                // Expression "[" Expression "]"
+                TokenKind::LeftBracket => {
+                    self.input.next();
+
+                    let index = match self.parse_nested_expression() {
+                        ParseResult::Err(err) => return ParseResult::Err(err),
+                        ParseResult::Ok((index, _)) => index,
+                    };
+
+                    let end = match self.parse_token(TokenKind::RightBracket) {
+                        ParseResult::Err(err) => return ParseResult::Err(err),
+                        ParseResult::Ok((_, end)) => end,
+                    };
+
+                    (
+                        ExpressionKind::Index {
+                            base: Box::new(expression),
+                            index: Box::new(index),
+                        },
+                        end,
+                    )
+                }
+                // Expression "." Identifier
+                TokenKind::Dot => {
+                    self.input.next();
+
+                    let (member, end) = self.parse_identifier()?;
+
+                    (
+                        ExpressionKind::Member {
+                            base: Box::new(expression),
+                            member,
+                        },
+                        end,
+                    )
+                }
+                _ => break,
+            };
+
+            // Postfix expressions always start where their innermost operand does.
+            expression = Expression::new(kind, start.to(self.span(&end)));
+            position = end;
+        }
+
+        ParseResult::Ok((expression, position))
+    }
+
+    /// Parses the comma separated arguments of a call, up to and including the closing `)`.
+    /// A trailing comma is allowed.
+    fn parse_arguments(&mut self) -> ParseResult<Vec<Expression>> {
+        let mut arguments = Vec::new();
+
+        loop {
+            if let ParseResult::Ok((_, end)) = self.parse_token(TokenKind::RightParenthesis) {
+                return ParseResult::Ok((arguments, end));
+            }
+
+            match self.parse_nested_expression() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((argument, _)) => arguments.push(argument),
+            }
+
+            if self.parse_token(TokenKind::Comma).is_err() {
+                return match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(_) => ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, end)) => ParseResult::Ok((arguments, end)),
+                };
+            }
+        }
+    }
+
+    fn parse_primary_expression(&mut self) -> ParseResult<Expression> {
+        self.input.push();
+
+        let token = match self.input.next() {
+            None => {
+                self.input.drop();
+                self.expect(Expected::Production("expression"));
+                return ParseResult::Err(ParseError::unexpected(self));
+            }
+            Some(token) => token,
+        };
+
+        let span = self.span(&token.position);
+
+        let kind = match token.kind {
+            TokenKind::IntegerLiteral(kind, value, _) => {
+                ExpressionKind::IntegerLiteral(kind, value)
+            }
+            TokenKind::CharLiteral(c) => ExpressionKind::CharLiteral(c),
+            TokenKind::StringLiteral(literal) => ExpressionKind::StringLiteral(literal),
+            TokenKind::Illegal => ExpressionKind::Error,
+            // Identifier "{" ( Identifier ":" Expression "," )* "}"
+            TokenKind::Identifier(identifier)
+                if self.struct_literals
+                    && matches!(
+                        self.input.current(),
+                        Some(Token {
+                            kind: TokenKind::LeftBrace,
+                            ..
+                        })
+                    ) =>
+            {
+                self.input.drop();
+
+                return self.parse_struct_literal(String::from(identifier), span);
+            }
+            TokenKind::Identifier(identifier) => {
+                ExpressionKind::Identifier(String::from(identifier))
+            }
+            // "[" ( Expression "," )* "]"
+            // "[" Expression ";" Expression "]"
+            TokenKind::LeftBracket => {
+                self.input.drop();
+
+                return self.parse_array_literal(span);
+            }
+            // "sizeof" "(" Type ")"
+            TokenKind::Keyword(Keyword::SizeOf) => {
+                self.input.drop();
+
+                match self.parse_token(TokenKind::LeftParenthesis) {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok(_) => (),
+                }
+
+                let ty = match self.parse_type() {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((ty, _)) => ty,
+                };
+
+                return match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(err) => ParseResult::Err(err),
+                    ParseResult::Ok((_, end)) => ParseResult::Ok((
+                        Expression::new(ExpressionKind::SizeOf(ty), span.to(self.span(&end))),
+                        end,
+                    )),
+                };
+            }
+            // "(" Expression ")"
+            TokenKind::LeftParenthesis => {
+                self.input.drop();
+
+                let expression = match self.parse_nested_expression() {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((expression, _)) => expression,
+                };
+
+                return match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(err) => ParseResult::Err(err),
+                    ParseResult::Ok((_, end)) => ParseResult::Ok((
+                        Expression::new(expression.kind, span.to(self.span(&end))),
+                        end,
+                    )),
+                };
+            }
+            _ => {
+                self.input.pop();
+                self.expect(Expected::Production("expression"));
+                return ParseResult::Err(ParseError::unexpected(self));
+            }
+        };
+
+        self.input.drop();
+        ParseResult::Ok((Expression::new(kind, span), token.position))
+    }
+
+    fn parse_array_literal(&mut self, start: Span) -> ParseResult<Expression> {
+        // The opening "[" has already been consumed.
+        let mut elements = Vec::new();
+
+        let end = loop {
+            // "]"
+            if let ParseResult::Ok((_, end)) = self.parse_token(TokenKind::RightBracket) {
+                break end;
+            }
+
+            // Expression
+            let element = match self.parse_nested_expression() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((element, _)) => element,
+            };
+
+            // ";" Expression "]"
+            if elements.is_empty() && self.parse_token(TokenKind::SemiColon).is_ok() {
+                let count = match self.parse_nested_expression() {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((count, _)) => count,
+                };
+
+                return match self.parse_token(TokenKind::RightBracket) {
+                    ParseResult::Err(err) => ParseResult::Err(err),
+                    ParseResult::Ok((_, end)) => ParseResult::Ok((
+                        Expression::new(
+                            ExpressionKind::ArrayRepeat {
+                                value: Box::new(element),
+                                count: Box::new(count),
+                            },
+                            self.span_from(start),
+                        ),
+                        end,
+                    )),
+                };
+            }
+
+            elements.push(element);
+
+            // ","
+            // The comma after the last element is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                match self.parse_token(TokenKind::RightBracket) {
+                    ParseResult::Err(_) => return ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, end)) => break end,
+                }
+            }
+        };
+
+        ParseResult::Ok((
+            Expression::new(
+                ExpressionKind::ArrayLiteral(elements),
+                self.span_from(start),
+            ),
+            end,
+        ))
+    }
+
+    fn parse_struct_literal(&mut self, name: String, start: Span) -> ParseResult<Expression> {
+        // The name has already been consumed.
+
+        // "{"
+        match self.parse_token(TokenKind::LeftBrace) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let mut fields = Vec::new();
+
+        let end = loop {
+            // "}"
+            if let ParseResult::Ok((_, end)) = self.parse_token(TokenKind::RightBrace) {
+                break end;
+            }
+
+            match self.parse_field_initializer() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((field, _)) => fields.push(field),
+            }
+
+            // ","
+            // The comma after the last field is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                match self.parse_token(TokenKind::RightBrace) {
+                    ParseResult::Err(_) => return ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, end)) => break end,
+                }
+            }
         };
 
-        ParseResult::Ok((TypeDeclaration { identifier, ty }, position))
+        ParseResult::Ok((
+            Expression::new(
+                ExpressionKind::StructLiteral { name, fields },
+                self.span_from(start),
+            ),
+            end,
+        ))
     }
 
-    fn parse_expression(&mut self) -> ParseResult<Expression> {
-        match self.parse_token(TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 5)) {
+    fn parse_field_initializer(&mut self) -> ParseResult<FieldInitializer> {
+        let start = self.current_span();
+
+        // Identifier ":" Expression
+
+        // Identifier
+        let identifier = match self.parse_identifier() {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok((identifier, _)) => identifier,
+        };
+
+        // ":"
+        match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        // Expression
+        match self.parse_nested_expression() {
             ParseResult::Err(err) => ParseResult::Err(err),
-            ParseResult::Ok((expr, position)) => ParseResult::Ok((
-                Expression::IntegerLiteral(IntegerLiteralKind::Decimal, 5),
+            ParseResult::Ok((value, position)) => ParseResult::Ok((
+                FieldInitializer {
+                    identifier,
+                    value,
+                    span: self.span_from(start),
+                },
                 position,
             )),
         }
     }
 
+    /// Parses a type and any postfix `*` and `[N]` after it. These apply left to right, so `u8*[4]`
+    /// is an array of four pointers to `u8`. A function type's return type takes every postfix
+    /// after it, so `fn(): u8*` returns a pointer rather than being one.
     fn parse_type(&mut self) -> ParseResult<Type> {
+        self.parse_postfix_type(false)
+    }
+
+    /// Parses a type like `parse_type`. After `as`, the type is followed by the rest of an
+    /// expression, so a `*` followed by the start of an operand is a multiplication rather than a
+    /// pointer type: `x as u8 * y` multiplies. Where the operand could also continue a pointer
+    /// type's expression, as in `p as u8* - 1` or `x as u8 * *p`, the `*` is reported as ambiguous
+    /// instead of picking either reading.
+    fn parse_postfix_type(&mut self, after_as: bool) -> ParseResult<Type> {
+        let start = self.current_span();
+
+        // Type ( "*" | "[" Expression "]" )*
+
+        let (mut ty, mut position) = self.parse_base_type(after_as)?;
+
+        loop {
+            self.input.push();
+
+            // "*"
+            if let ParseResult::Ok((_, end)) = self.parse_token(TokenKind::Asterisk) {
+                if after_as {
+                    match self.operand_after_asterisks() {
+                        None => (),
+                        Some((0, next, _)) if !is_ambiguous_after_pointer(&next) => {
+                            self.input.pop();
+                            break;
+                        }
+                        Some((count, next, next_position)) => {
+                            // With more `*`s, it's the second one that could be either.
+                            let next = match count {
+                                0 => next,
+                                _ => TokenKind::Asterisk,
+                            };
+                            let span = self.span(&end).to(self.span(&next_position));
+                            self.input.drop();
+                            return ParseResult::Err(ParseError::ambiguous_cast(&next, span));
+                        }
+                    }
+                }
+
+                self.input.drop();
+                ty = Type::new(TypeKind::Pointer(Box::new(ty)), self.span_from(start));
+                position = end;
+                continue;
+            }
+
+            self.input.drop();
+
+            // "[" Expression "]"
+            if self.parse_token(TokenKind::LeftBracket).is_err() {
+                break;
+            }
+
+            let size = match self.parse_expression() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((size, _)) => size,
+            };
+
+            match self.parse_token(TokenKind::RightBracket) {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((_, end)) => position = end,
+            }
+
+            ty = Type::new(
+                TypeKind::Array(Box::new(ty), Box::new(size)),
+                self.span_from(start),
+            );
+        }
+
+        ParseResult::Ok((ty, position))
+    }
+
+    /// Counts any further `*`s after the one just consumed, and returns that count along with the
+    /// token after them if it starts an operand. Consumes nothing.
+    fn operand_after_asterisks(&mut self) -> Option<(usize, TokenKind<'a>, TokenPosition)> {
+        self.input.push();
+
+        let mut count = 0;
+        while let Some(Token {
+            kind: TokenKind::Asterisk,
+            ..
+        }) = self.input.current()
+        {
+            self.input.next();
+            count += 1;
+        }
+
+        let result = match self.input.current() {
+            Some(token) if starts_operand(&token.kind) => Some((count, token.kind, token.position)),
+            _ => None,
+        };
+        self.input.pop();
+
+        result
+    }
+
+    fn parse_base_type(&mut self, after_as: bool) -> ParseResult<Type> {
+        let start = self.current_span();
+
+        // Report a missing type as such, rather than listing every token that can start one.
+        if !self
+            .input
+            .current()
+            .is_some_and(|token| starts_type(&token.kind))
+        {
+            self.expect(Expected::Production("type"));
+            return ParseResult::Err(ParseError::unexpected(self));
+        }
+
         let primitives = vec![
-            (Keyword::U8, Type::U8),
-            (Keyword::I8, Type::I8),
-            (Keyword::U16, Type::U16),
-            (Keyword::I16, Type::I16),
+            (Keyword::U8, TypeKind::U8),
+            (Keyword::I8, TypeKind::I8),
+            (Keyword::U16, TypeKind::U16),
+            (Keyword::I16, TypeKind::I16),
         ];
 
         for (primitive, kind) in primitives {
             match self.parse_keyword(primitive) {
                 ParseResult::Err(_) => continue,
-                ParseResult::Ok((_, position)) => return ParseResult::Ok((kind, position)),
+                ParseResult::Ok((_, position)) => {
+                    return ParseResult::Ok((Type::new(kind, self.span_from(start)), position))
+                }
             }
         }
 
-        ParseResult::Err(ParseError::unexpected_token(self))
+        // Identifier
+        if let ParseResult::Ok((identifier, position)) = self.parse_identifier() {
+            return ParseResult::Ok((
+                Type::new(TypeKind::Named(identifier), self.span_from(start)),
+                position,
+            ));
+        }
+
+        // "fn" "(" ( Type "," )* ")" ( ":" Type )?
+        // "fn" "(" ( Type "," )+ "..." ")" ( ":" Type )?
+        match self.parse_keyword(Keyword::Fn) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        match self.parse_token(TokenKind::LeftParenthesis) {
+            ParseResult::Err(err) => return ParseResult::Err(err),
+            ParseResult::Ok(_) => (),
+        }
+
+        let mut parameters = Vec::new();
+        let mut is_variadic = false;
+
+        let mut position = loop {
+            // ")"
+            if let ParseResult::Ok((_, end)) = self.parse_token(TokenKind::RightParenthesis) {
+                break end;
+            }
+
+            // "..." ")"
+            if !parameters.is_empty() && self.parse_token(TokenKind::Ellipsis).is_ok() {
+                is_variadic = true;
+
+                match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(err) => return ParseResult::Err(err),
+                    ParseResult::Ok((_, end)) => break end,
+                }
+            }
+
+            // Type
+            match self.parse_type() {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((parameter, _)) => parameters.push(parameter),
+            }
+
+            // ","
+            // The comma after the last parameter is optional.
+            if self.parse_token(TokenKind::Comma).is_err() {
+                match self.parse_token(TokenKind::RightParenthesis) {
+                    ParseResult::Err(_) => return ParseResult::Err(ParseError::unexpected(self)),
+                    ParseResult::Ok((_, end)) => break end,
+                }
+            }
+        };
+
+        // ( ":" Type )?
+        // The return type takes every postfix after it, so after `as` it ends where the cast does.
+        let return_type = match self.parse_token(TokenKind::Colon) {
+            ParseResult::Err(_) => None,
+            ParseResult::Ok(_) => match self.parse_postfix_type(after_as) {
+                ParseResult::Err(err) => return ParseResult::Err(err),
+                ParseResult::Ok((ty, end)) => {
+                    position = end;
+                    Some(Box::new(ty))
+                }
+            },
+        };
+
+        ParseResult::Ok((
+            Type::new(
+                TypeKind::Function {
+                    parameters,
+                    is_variadic,
+                    return_type,
+                },
+                self.span_from(start),
+            ),
+            position,
+        ))
     }
 
     fn parse_keyword_with_identifier(&mut self, target: Keyword) -> ParseResult<String> {
@@ -506,6 +2532,8 @@ impl Parser {
     }
 
     fn parse_field(&mut self) -> ParseResult<Field> {
+        let start = self.current_span();
+
         // Field
         // Identifier ":" Type
 
@@ -526,6 +2554,7 @@ impl Parser {
                         Field {
                             identifier,
                             field_type,
+                            span: self.span_from(start),
                         },
                         remaining,
                     ))
@@ -538,43 +2567,39 @@ impl Parser {
         self.input.push();
 
         if let Some(token) = self.input.next() {
-            if let TokenKind::Keyword(keyword) = token.kind {
-                if keyword == target {
-                    self.input.drop();
-                    ParseResult::Ok(((), token.position))
-                } else {
-                    self.input.pop();
-                    ParseResult::Err(ParseError::unexpected_token(self))
-                }
-            } else {
-                self.input.pop();
-                ParseResult::Err(ParseError::unexpected_token(self))
+            if token.kind == TokenKind::Keyword(target.clone()) {
+                self.input.drop();
+                return ParseResult::Ok(((), token.position));
             }
+
+            self.input.pop();
         } else {
             self.input.drop();
-            ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedEOF))
         }
+
+        self.expect(Expected::Token(TokenKind::Keyword(target)));
+        ParseResult::Err(ParseError::unexpected(self))
     }
 
-    fn parse_token(&mut self, target: TokenKind) -> ParseResult<()> {
+    fn parse_token(&mut self, target: TokenKind<'static>) -> ParseResult<()> {
         self.input.push();
 
-        // Fetch the next token. If it exists:
+        // Fetch the next token. If it exists and matches our target, then return that success.
         if let Some(token) = self.input.next() {
-            // If this token matches our target, then return that success.
-            if target == token.kind {
+            if token.kind == target {
                 self.input.drop();
-                ParseResult::Ok(((), token.position))
-            // Otherwise, the fetched token didn't match the target and parsing fails.
-            } else {
-                self.input.pop();
-                ParseResult::Err(ParseError::unexpected_token(self))
+                return ParseResult::Ok(((), token.position));
             }
-        // Otherwise, if there wasn't another token, then we've unexpectedly reached the end of our input.
+
+            self.input.pop();
         } else {
             self.input.drop();
-            ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedEOF))
         }
+
+        // Otherwise, the next token didn't match the target, or we've unexpectedly reached the end
+        // of our input, and parsing fails.
+        self.expect(Expected::Token(target));
+        ParseResult::Err(ParseError::unexpected(self))
     }
 
     fn parse_identifier(&mut self) -> ParseResult<String> {
@@ -583,31 +2608,15 @@ impl Parser {
         if let Some(token) = self.input.next() {
             if let TokenKind::Identifier(identifier) = token.kind {
                 self.input.drop();
-                ParseResult::Ok((identifier, token.position))
-            } else {
-                self.input.pop();
-                ParseResult::Err(ParseError::unexpected_token(self))
+                return ParseResult::Ok((String::from(identifier), token.position));
             }
-        } else {
-            self.input.drop();
-            ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedEOF))
-        }
-    }
-
-    fn parse_whitespace(&mut self) -> ParseResult<usize> {
-        self.input.push();
 
-        if let Some(token) = self.input.next() {
-            if let TokenKind::Whitespace(line_break_count) = token.kind {
-                self.input.drop();
-                ParseResult::Ok((line_break_count, token.position))
-            } else {
-                self.input.pop();
-                ParseResult::Err(ParseError::unexpected_token(self))
-            }
+            self.input.pop();
         } else {
             self.input.drop();
-            ParseResult::Err(ParseError::from_kind(self, ParseErrorKind::UnexpectedEOF))
         }
+
+        self.expect(Expected::Production("identifier"));
+        ParseResult::Err(ParseError::unexpected(self))
     }
 }
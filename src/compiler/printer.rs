@@ -0,0 +1,172 @@
+//! Prints expressions and types back as Opal source. Parentheses are only added where the operator
+//! table says they are needed, so printing a parsed expression and parsing it again gives back the
+//! same tree.
+
+use super::operators::{Associativity, POSTFIX_PRECEDENCE, UNARY_PRECEDENCE};
+use super::parser::{Expression, ExpressionKind, Type, TypeKind, UnaryOperator};
+use super::{write_escaped, IntegerLiteralKind};
+use std::fmt;
+
+impl Expression {
+    /// How tightly the expression binds, on the same scale as the operator table.
+    pub fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExpressionKind::Binary(operator, _, _) => operator.precedence(),
+            ExpressionKind::Unary(_, _) | ExpressionKind::Cast { .. } => UNARY_PRECEDENCE,
+            _ => POSTFIX_PRECEDENCE,
+        }
+    }
+}
+
+/// Writes `expression`, wrapped in parentheses if it binds looser than `min_precedence`.
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    expression: &Expression,
+    min_precedence: u8,
+) -> fmt::Result {
+    if expression.precedence() < min_precedence {
+        write!(f, "({})", expression)
+    } else {
+        write!(f, "{}", expression)
+    }
+}
+
+/// Writes the callee, base or object of a postfix expression. A struct literal there is always
+/// parenthesized: a condition can't contain a bare struct literal, so `Point { x: 1 }.x` would not
+/// parse back inside one.
+fn write_postfix_operand(f: &mut fmt::Formatter<'_>, expression: &Expression) -> fmt::Result {
+    match expression.kind {
+        ExpressionKind::StructLiteral { .. } => write!(f, "({})", expression),
+        _ => write_operand(f, expression, POSTFIX_PRECEDENCE),
+    }
+}
+
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExpressionKind::IntegerLiteral(IntegerLiteralKind::Decimal, value) => {
+                write!(f, "{}", value)
+            }
+            ExpressionKind::IntegerLiteral(IntegerLiteralKind::Hexadecimal, value) => {
+                write!(f, "0x{:X}", value)
+            }
+            ExpressionKind::IntegerLiteral(IntegerLiteralKind::Binary, value) => {
+                write!(f, "0b{:b}", value)
+            }
+            ExpressionKind::CharLiteral(c) => {
+                f.write_str("'")?;
+                write_escaped(f, *c, b'\'')?;
+                f.write_str("'")
+            }
+            ExpressionKind::StringLiteral(literal) => {
+                f.write_str("\"")?;
+                for &c in literal {
+                    write_escaped(f, c, b'"')?;
+                }
+                f.write_str("\"")
+            }
+            ExpressionKind::Identifier(identifier) => f.write_str(identifier),
+            ExpressionKind::Error => f.write_str("<error>"),
+            ExpressionKind::SizeOf(ty) => write!(f, "sizeof({})", ty),
+            ExpressionKind::ArrayLiteral(elements) => {
+                f.write_str("[")?;
+                write_list(f, elements)?;
+                f.write_str("]")
+            }
+            ExpressionKind::ArrayRepeat { value, count } => write!(f, "[{}; {}]", value, count),
+            ExpressionKind::StructLiteral { name, fields } => {
+                write!(f, "{} {{", name)?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, " {}: {}", field.identifier, field.value)?;
+                }
+                f.write_str(" }")
+            }
+            ExpressionKind::Call { callee, arguments } => {
+                write_postfix_operand(f, callee)?;
+                f.write_str("(")?;
+                write_list(f, arguments)?;
+                f.write_str(")")
+            }
+            ExpressionKind::Index { base, index } => {
+                write_postfix_operand(f, base)?;
+                write!(f, "[{}]", index)
+            }
+            ExpressionKind::Member { base, member } => {
+                write_postfix_operand(f, base)?;
+                write!(f, ".{}", member)
+            }
+            ExpressionKind::Cast { ty, expression } => {
+                write!(f, "<{}>", ty)?;
+                write_operand(f, expression, UNARY_PRECEDENCE)
+            }
+            ExpressionKind::Unary(operator, operand) => {
+                write!(f, "{}", operator)?;
+
+                // `& &x` would otherwise run together into a `&&` token.
+                if let ExpressionKind::Unary(UnaryOperator::AddressOf, _) = operand.kind {
+                    if *operator == UnaryOperator::AddressOf {
+                        f.write_str(" ")?;
+                    }
+                }
+
+                write_operand(f, operand, UNARY_PRECEDENCE)
+            }
+            ExpressionKind::Binary(operator, lhs, rhs) => {
+                // An operand on the side the operator doesn't group towards needs parentheses even
+                // at the operator's own precedence.
+                let (lhs_precedence, rhs_precedence) = match operator.associativity() {
+                    Associativity::Left => (operator.precedence(), operator.precedence() + 1),
+                    Associativity::Right => (operator.precedence() + 1, operator.precedence()),
+                };
+
+                write_operand(f, lhs, lhs_precedence)?;
+                write!(f, " {} ", operator)?;
+                write_operand(f, rhs, rhs_precedence)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TypeKind::U8 => f.write_str("u8"),
+            TypeKind::I8 => f.write_str("i8"),
+            TypeKind::U16 => f.write_str("u16"),
+            TypeKind::I16 => f.write_str("i16"),
+            TypeKind::Named(name) => f.write_str(name),
+            TypeKind::Pointer(pointee) => write!(f, "{}*", pointee),
+            TypeKind::Array(element, size) => write!(f, "{}[{}]", element, size),
+            TypeKind::Function {
+                parameters,
+                is_variadic,
+                return_type,
+            } => {
+                f.write_str("fn(")?;
+                write_list(f, parameters)?;
+                if *is_variadic {
+                    f.write_str(", ...")?;
+                }
+                f.write_str(")")?;
+                if let Some(return_type) = return_type {
+                    write!(f, ": {}", return_type)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{next_column, TokenPosition, DEFAULT_TAB_WIDTH};
+
+/// Identifies a file loaded into a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(u32);
+
+/// A byte range within one file of a `SourceMap`. Spans are small and `Copy` so that they can be
+/// stored on every token, AST node and diagnostic; use the `SourceMap` to turn them back into
+/// file names, lines and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub file: FileId,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Span {
+    pub fn new(file: FileId, start: usize, end: usize) -> Self {
+        Self {
+            file,
+            start: start as u32,
+            end: end as u32,
+        }
+    }
+
+    /// The smallest span covering both `self` and `other`, which must be in the same file.
+    pub fn to(self, other: Span) -> Span {
+        debug_assert_eq!(self.file, other.file);
+
+        Span {
+            file: self.file,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        (self.end - self.start) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl TokenPosition {
+    pub fn to_span(&self, file: FileId) -> Span {
+        Span::new(file, self.start.absolute, self.end.absolute)
+    }
+}
+
+/// A human readable position, as resolved by `SourceMap::location`. `line` and `column` follow the
+/// same model as `TextPosition`, but are displayed 1-based as editors expect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location<'a> {
+    pub file_name: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.file_name,
+            self.line + 1,
+            self.column + 1
+        )
+    }
+}
+
+struct SourceFile {
+    name: String,
+    source: String,
+    // Byte offset at which each line starts, so that lookups are a binary search.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(name: String, source: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            name,
+            source,
+            line_starts,
+        }
+    }
+
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    fn line(&self, line: usize) -> &str {
+        let start = self.line_starts[line];
+        let end = match self.line_starts.get(line + 1) {
+            Some(&next) => next,
+            None => self.source.len(),
+        };
+
+        self.source[start..end].trim_end_matches(['\n', '\r'])
+    }
+}
+
+/// Owns the text of every file in a compilation and resolves `Span`s back into them.
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    tab_width: usize,
+}
+
+impl Default for SourceMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+
+    /// Columns are only consistent with the lexer's if both use the same tab width. Panics if
+    /// `tab_width` is zero.
+    pub fn with_tab_width(self, tab_width: usize) -> Self {
+        assert!(tab_width > 0, "tab width must be at least 1");
+        Self { tab_width, ..self }
+    }
+
+    pub fn add_file(&mut self, name: &str, source: String) -> FileId {
+        self.files.push(SourceFile::new(String::from(name), source));
+
+        FileId((self.files.len() - 1) as u32)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> io::Result<FileId> {
+        let source = fs::read_to_string(path)?;
+
+        Ok(self.add_file(&path.to_string_lossy(), source))
+    }
+
+    pub fn name(&self, file: FileId) -> &str {
+        &self.file(file).name
+    }
+
+    pub fn source(&self, file: FileId) -> &str {
+        &self.file(file).source
+    }
+
+    /// The location of the start of `span`.
+    pub fn location(&self, span: Span) -> Location<'_> {
+        let file = self.file(span.file);
+        let offset = span.start as usize;
+        let line = file.line_index(offset);
+
+        let line_start = file.line_starts[line];
+        let mut chars = file.source[line_start..offset].chars().peekable();
+        let mut column = 0;
+
+        // The character after the prefix still matters, since it decides how a `\r` is counted.
+        let following = file.source[offset..].chars().next();
+
+        while let Some(c) = chars.next() {
+            let next = chars.peek().copied().or(following);
+            column = next_column(column, c, next, self.tab_width);
+        }
+
+        Location {
+            file_name: &file.name,
+            line,
+            column,
+        }
+    }
+
+    /// The exact source text covered by `span`.
+    pub fn snippet(&self, span: Span) -> &str {
+        &self.file(span.file).source[span.start as usize..span.end as usize]
+    }
+
+    /// The full text of the line on which `span` starts, without its line break.
+    pub fn line(&self, span: Span) -> &str {
+        let file = self.file(span.file);
+
+        file.line(file.line_index(span.start as usize))
+    }
+
+    fn file(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0 as usize]
+    }
+}
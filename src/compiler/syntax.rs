@@ -0,0 +1,656 @@
+//! A lossless concrete syntax tree. Unlike the AST, it keeps every token of the source, including
+//! parentheses, commas, whitespace and comments, so printing it gives back the source exactly.
+//!
+//! The tree comes in two layers. Green nodes are immutable and only know their kind, width and
+//! children, so unchanged subtrees can be shared between edits. Red `SyntaxNode`s wrap them with
+//! their parent and absolute offset for navigation. Editing a node or token produces a new root,
+//! leaving the old tree untouched.
+//!
+//! Typed views of the nodes, in the `ast` module, are available through `SyntaxNode::cast`.
+
+use super::ast::AstNode;
+use super::lexer::{Lexer, Token, TokenKind, TokenPosition};
+use super::parser::*;
+use super::source_map::{FileId, Span};
+use std::cmp::Reverse;
+use std::fmt;
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::vec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    SourceFile,
+    ConstantDeclaration,
+    VariableDeclaration,
+    FunctionDeclaration,
+    StructDeclaration,
+    UnionDeclaration,
+    EnumDeclaration,
+    EnumVariant,
+    TypeDeclaration,
+    Field,
+    Assignment,
+    If,
+    While,
+    For,
+    Switch,
+    Case,
+    Break,
+    Continue,
+    Return,
+    Expression,
+    FieldInitializer,
+    Type,
+    /// Source that failed to parse and was skipped during error recovery.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenToken {
+    text: String,
+}
+
+impl GreenToken {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    width: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        Self {
+            kind,
+            width: children.iter().map(GreenElement::width).sum(),
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// The length of the node's text in bytes.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.fmt(f)?,
+                GreenElement::Token(token) => f.write_str(&token.text)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A node of the syntax tree, positioned within its file.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    file: FileId,
+    parent: Option<SyntaxNode>,
+    // The index of this node among its parent's children.
+    index: usize,
+    offset: usize,
+}
+
+/// A token or piece of trivia in the syntax tree, positioned within its file.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(file: FileId, green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            file,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn file(&self) -> FileId {
+        self.0.file
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(
+            self.0.file,
+            self.0.offset,
+            self.0.offset + self.0.green.width,
+        )
+    }
+
+    /// The source text of the node, including any parentheses, commas and comments inside it.
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+
+        self.0
+            .green
+            .children
+            .iter()
+            .enumerate()
+            .map(|(index, child)| {
+                let element = match child {
+                    GreenElement::Node(green) => {
+                        SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                            green: green.clone(),
+                            file: self.0.file,
+                            parent: Some(self.clone()),
+                            index,
+                            offset,
+                        })))
+                    }
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        parent: self.clone(),
+                        index,
+                        offset,
+                    }),
+                };
+                offset += child.width();
+
+                element
+            })
+            .collect()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// This node and every node below it, in source order.
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut result = vec![self.clone()];
+
+        for child in self.children() {
+            result.extend(child.descendants());
+        }
+
+        result
+    }
+
+    /// Every token and piece of trivia below this node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut result = Vec::new();
+
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => result.extend(node.tokens()),
+                SyntaxElement::Token(token) => result.push(token),
+            }
+        }
+
+        result
+    }
+
+    /// Views the node as the `ast` type `T`, if it is a node of that kind.
+    pub fn cast<T: AstNode>(&self) -> Option<T> {
+        if T::can_cast(self.kind()) {
+            Some(T::from_syntax(self.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the root of a new tree with this node replaced by `green`. The existing tree is left
+    /// as it was.
+    pub fn replace_with(&self, green: Rc<GreenNode>) -> SyntaxNode {
+        match &self.0.parent {
+            None => SyntaxNode::new_root(self.0.file, green),
+            Some(parent) => parent.replace_child(self.0.index, GreenElement::Node(green)),
+        }
+    }
+
+    fn replace_child(&self, index: usize, child: GreenElement) -> SyntaxNode {
+        let mut children = self.0.green.children.clone();
+        children[index] = child;
+
+        self.replace_with(Rc::new(GreenNode::new(self.kind(), children)))
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{}..{}",
+            self.kind(),
+            self.0.offset,
+            self.0.offset + self.0.green.width
+        )
+    }
+}
+
+impl SyntaxToken {
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    /// The kind of the token, lexed again from its text.
+    pub fn kind(&self) -> TokenKind<'_> {
+        match Lexer::new(&self.green.text).next_token() {
+            Ok(token) => token.kind,
+            Err(_) => TokenKind::Illegal,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(
+            self.parent.0.file,
+            self.offset,
+            self.offset + self.green.text.len(),
+        )
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// Returns the root of a new tree with this token's text replaced by `text`. The existing tree
+    /// is left as it was.
+    pub fn replace_with(&self, text: &str) -> SyntaxNode {
+        self.parent.replace_child(
+            self.index,
+            GreenElement::Token(Rc::new(GreenToken::new(text))),
+        )
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{}..{}",
+            self.green.text,
+            self.offset,
+            self.offset + self.green.text.len()
+        )
+    }
+}
+
+/// Builds the syntax tree for `source` from its tokens, as returned by `tokenize`, and the
+/// statements parsed from them. The AST decides where nodes start and end: every AST node becomes
+/// a syntax node covering the tokens within its span. Trivia before a node's first token or after
+/// its last belongs to the enclosing node.
+pub fn build_syntax_tree(
+    file: FileId,
+    source: &str,
+    tokens: &[Token<'_>],
+    statements: &[Statement],
+) -> SyntaxNode {
+    let mut nodes = Vec::new();
+    for statement in statements {
+        collect_statement(statement, &mut nodes);
+    }
+
+    // Enclosing nodes sort before the nodes inside them. The sort is stable, so a node keeps coming
+    // before any child that has exactly the same span.
+    nodes.sort_by_key(|(_, span)| (span.start, Reverse(span.end)));
+
+    let mut builder = TreeBuilder {
+        source,
+        nodes: nodes.into_iter().peekable(),
+        stack: vec![(SyntaxKind::SourceFile, u32::MAX, Vec::new())],
+    };
+
+    for token in tokens {
+        for trivia in &token.leading_trivia {
+            builder.token(&trivia.position);
+        }
+
+        if token.kind != TokenKind::EOF {
+            builder.token(&token.position);
+        }
+
+        for trivia in &token.trailing_trivia {
+            builder.token(&trivia.position);
+        }
+    }
+
+    SyntaxNode::new_root(file, Rc::new(builder.finish()))
+}
+
+struct TreeBuilder<'a> {
+    source: &'a str,
+    // Nodes that have yet to be opened, by where they start.
+    nodes: Peekable<vec::IntoIter<(SyntaxKind, Span)>>,
+    // The open nodes, with where each one ends and the children it has so far.
+    stack: Vec<(SyntaxKind, u32, Vec<GreenElement>)>,
+}
+
+impl TreeBuilder<'_> {
+    fn token(&mut self, position: &TokenPosition) {
+        let start = position.start.absolute as u32;
+
+        self.close_before(start);
+
+        while let Some((kind, span)) = self.nodes.next_if(|(_, span)| span.start <= start) {
+            self.stack.push((kind, span.end, Vec::new()));
+            // A node that covers no tokens closes as soon as it opens.
+            self.close_before(start);
+        }
+
+        let text = &self.source[position.start.absolute..position.end.absolute];
+        self.push(GreenElement::Token(Rc::new(GreenToken::new(text))));
+    }
+
+    /// Closes every open node that ends at or before `offset`.
+    fn close_before(&mut self, offset: u32) {
+        while self.stack.len() > 1 && self.stack.last().is_some_and(|(_, end, _)| *end <= offset) {
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some((kind, _, children)) = self.stack.pop() {
+            self.push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+        }
+    }
+
+    fn push(&mut self, element: GreenElement) {
+        if let Some((_, _, children)) = self.stack.last_mut() {
+            children.push(element);
+        }
+    }
+
+    fn finish(mut self) -> GreenNode {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+
+        // Anything left starts at the very end of the file, so it is empty.
+        while let Some((kind, _)) = self.nodes.next() {
+            self.push(GreenElement::Node(Rc::new(GreenNode::new(
+                kind,
+                Vec::new(),
+            ))));
+        }
+
+        let (kind, _, children) = self.stack.pop().expect("the root node is always open");
+        GreenNode::new(kind, children)
+    }
+}
+
+fn collect_statement(statement: &Statement, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    match statement {
+        Statement::ConstantDeclaration(decl) => {
+            collect_constant_variable(SyntaxKind::ConstantDeclaration, decl, nodes)
+        }
+        Statement::VariableDeclaration(decl) => {
+            collect_constant_variable(SyntaxKind::VariableDeclaration, decl, nodes)
+        }
+        Statement::FunctionDeclaration(decl) => {
+            nodes.push((SyntaxKind::FunctionDeclaration, decl.span));
+            for parameter in &decl.parameters {
+                collect_field(parameter, nodes);
+            }
+            if let Some(return_type) = &decl.return_type {
+                collect_type(return_type, nodes);
+            }
+            for statement in decl.body.iter().flatten() {
+                collect_body_statement(statement, nodes);
+            }
+        }
+        Statement::StructDeclaration(decl) | Statement::UnionDeclaration(decl) => {
+            let kind = match decl.is_union {
+                false => SyntaxKind::StructDeclaration,
+                true => SyntaxKind::UnionDeclaration,
+            };
+            nodes.push((kind, decl.span));
+            for field in decl.fields.iter().flatten() {
+                collect_field(field, nodes);
+            }
+        }
+        Statement::EnumDeclaration(decl) => {
+            nodes.push((SyntaxKind::EnumDeclaration, decl.span));
+            for variant in decl.variants.iter().flatten() {
+                nodes.push((SyntaxKind::EnumVariant, variant.span));
+                if let Some(value) = &variant.value {
+                    collect_expression(value, nodes);
+                }
+            }
+        }
+        Statement::TypeDeclaration(decl) => collect_type_declaration(decl, nodes),
+        Statement::Error(span) => nodes.push((SyntaxKind::Error, *span)),
+    }
+}
+
+fn collect_body_statement(statement: &BodyStatement, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    match statement {
+        BodyStatement::TypeDeclaration(decl) => collect_type_declaration(decl, nodes),
+        BodyStatement::ConstantDeclaration(decl) => {
+            collect_constant_variable(SyntaxKind::ConstantDeclaration, decl, nodes)
+        }
+        BodyStatement::VariableDeclaration(decl) => {
+            collect_constant_variable(SyntaxKind::VariableDeclaration, decl, nodes)
+        }
+        BodyStatement::Assignment(statement) => {
+            nodes.push((SyntaxKind::Assignment, statement.span));
+            collect_expression(&statement.target, nodes);
+            collect_expression(&statement.value, nodes);
+        }
+        BodyStatement::If(statement) => {
+            nodes.push((SyntaxKind::If, statement.span));
+            collect_expression(&statement.condition, nodes);
+            for statement in statement
+                .body
+                .iter()
+                .chain(statement.else_body.iter().flatten())
+            {
+                collect_body_statement(statement, nodes);
+            }
+        }
+        BodyStatement::While(statement) => {
+            nodes.push((SyntaxKind::While, statement.span));
+            collect_expression(&statement.condition, nodes);
+            for statement in &statement.body {
+                collect_body_statement(statement, nodes);
+            }
+        }
+        BodyStatement::For(statement) => {
+            nodes.push((SyntaxKind::For, statement.span));
+            collect_field(&statement.variable, nodes);
+            collect_expression(&statement.start, nodes);
+            collect_expression(&statement.end, nodes);
+            if let Some(step) = &statement.step {
+                collect_expression(step, nodes);
+            }
+            for statement in &statement.body {
+                collect_body_statement(statement, nodes);
+            }
+        }
+        BodyStatement::Switch(statement) => {
+            nodes.push((SyntaxKind::Switch, statement.span));
+            collect_expression(&statement.value, nodes);
+            for case in &statement.cases {
+                nodes.push((SyntaxKind::Case, case.span));
+                if let Some(value) = &case.value {
+                    collect_expression(value, nodes);
+                }
+                for statement in &case.body {
+                    collect_body_statement(statement, nodes);
+                }
+            }
+        }
+        BodyStatement::Break(span) => nodes.push((SyntaxKind::Break, *span)),
+        BodyStatement::Continue(span) => nodes.push((SyntaxKind::Continue, *span)),
+        BodyStatement::Return(value, span) => {
+            nodes.push((SyntaxKind::Return, *span));
+            if let Some(value) = value {
+                collect_expression(value, nodes);
+            }
+        }
+        BodyStatement::Error(span) => nodes.push((SyntaxKind::Error, *span)),
+    }
+}
+
+fn collect_constant_variable(
+    kind: SyntaxKind,
+    decl: &ConstantVariableDeclaration,
+    nodes: &mut Vec<(SyntaxKind, Span)>,
+) {
+    nodes.push((kind, decl.span));
+    collect_type(&decl.const_type, nodes);
+    if let Some(value) = &decl.value {
+        collect_expression(value, nodes);
+    }
+}
+
+fn collect_type_declaration(decl: &TypeDeclaration, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::TypeDeclaration, decl.span));
+    collect_type(&decl.ty, nodes);
+}
+
+fn collect_field(field: &Field, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::Field, field.span));
+    collect_type(&field.field_type, nodes);
+}
+
+fn collect_type(ty: &Type, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::Type, ty.span));
+
+    match &ty.kind {
+        TypeKind::Pointer(pointee) => collect_type(pointee, nodes),
+        TypeKind::Array(element, size) => {
+            collect_type(element, nodes);
+            collect_expression(size, nodes);
+        }
+        TypeKind::Function {
+            parameters,
+            return_type,
+            ..
+        } => {
+            for parameter in parameters {
+                collect_type(parameter, nodes);
+            }
+            if let Some(return_type) = return_type {
+                collect_type(return_type, nodes);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn collect_expression(expression: &Expression, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::Expression, expression.span));
+
+    match &expression.kind {
+        ExpressionKind::SizeOf(ty) => collect_type(ty, nodes),
+        ExpressionKind::Call { callee, arguments } => {
+            collect_expression(callee, nodes);
+            for argument in arguments {
+                collect_expression(argument, nodes);
+            }
+        }
+        ExpressionKind::Index { base, index } => {
+            collect_expression(base, nodes);
+            collect_expression(index, nodes);
+        }
+        ExpressionKind::Member { base, .. } => collect_expression(base, nodes),
+        ExpressionKind::Cast { ty, expression } => {
+            collect_type(ty, nodes);
+            collect_expression(expression, nodes);
+        }
+        ExpressionKind::ArrayLiteral(elements) => {
+            for element in elements {
+                collect_expression(element, nodes);
+            }
+        }
+        ExpressionKind::ArrayRepeat { value, count } => {
+            collect_expression(value, nodes);
+            collect_expression(count, nodes);
+        }
+        ExpressionKind::StructLiteral { fields, .. } => {
+            for field in fields {
+                nodes.push((SyntaxKind::FieldInitializer, field.span));
+                collect_expression(&field.value, nodes);
+            }
+        }
+        ExpressionKind::Unary(_, operand) => collect_expression(operand, nodes),
+        ExpressionKind::Binary(_, lhs, rhs) => {
+            collect_expression(lhs, nodes);
+            collect_expression(rhs, nodes);
+        }
+        _ => (),
+    }
+}
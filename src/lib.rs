@@ -0,0 +1,13 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+#![allow(unused_mut)]
+#![warn(unused_must_use)]
+#![allow(clippy::single_match)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::match_single_binding)]
+#![allow(clippy::upper_case_acronyms)]
+#![allow(clippy::enum_variant_names)]
+#![allow(clippy::should_implement_trait)]
+
+pub mod compiler;
+mod tests;
@@ -1,23 +1,22 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unused_mut)]
-#![warn(unused_must_use)]
+#![allow(clippy::needless_return)]
 
-mod compiler;
-mod tests;
-
-use compiler::{tokenize};
+use opal::compiler::{tokenize, FileId};
 
 fn main() {
-    let tokens = match tokenize("# Gonna tokenize this whole thing!
-var foo: u8: 69;  # Can do inline comments too") {
+    match tokenize(
+        FileId::default(),
+        "# Gonna tokenize this whole thing!
+var foo: u8: 69;  # Can do inline comments too",
+    ) {
         Ok(tokens) => {
             for token in tokens {
                 println!("{:?}", token);
             }
-        },
-        Err(err) => {
-            println!("Tokenizing failed: {:?}", err);
+        }
+        Err((errors, _)) => {
+            for err in errors {
+                println!("Tokenizing failed: {:?}", err);
+            }
             return;
         }
     };
@@ -21,6 +21,8 @@ fn create_token_from_kind() {
                     column: 0,
                 },
             },
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         }
     );
 }
@@ -40,13 +42,20 @@ fn next_token_when_empty_input() {
     let mut lexer = Lexer::new("");
     assert_eq!(
         lexer.next_token(),
-        LexResult::Err(LexError::new(
-            LexErrorKind::InputExhausted,
-            TextPosition {
-                absolute: 0,
-                line: 0,
-                column: 0,
-            }
+        LexResult::Ok(Token::new(
+            TokenKind::EOF,
+            TokenPosition::new(
+                TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+            )
         ))
     )
 }
@@ -88,7 +97,7 @@ fn valid_char_literal() {
     assert_eq!(
         Lexer::new("'f'").next_token().unwrap(),
         Token::new(
-            TokenKind::CharLiteral('f'),
+            TokenKind::CharLiteral(b'f'),
             TokenPosition::new(
                 TextPosition {
                     absolute: 0,
@@ -96,9 +105,9 @@ fn valid_char_literal() {
                     column: 0,
                 },
                 TextPosition {
-                    absolute: 2,
+                    absolute: 3,
                     line: 0,
-                    column: 2,
+                    column: 3,
                 },
             ),
         )
@@ -130,7 +139,7 @@ fn decimal_integer_literal() {
     assert_eq!(
         Lexer::new("123").next_token().unwrap(),
         Token::new(
-            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 123),
+            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 123, "123"),
             TokenPosition {
                 start: TextPosition {
                     absolute: 0,
@@ -152,7 +161,7 @@ fn decimal_integer_literal_with_underscore() {
     assert_eq!(
         Lexer::new("12_3").next_token().unwrap(),
         Token::new(
-            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 123),
+            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 123, "12_3"),
             TokenPosition {
                 start: TextPosition {
                     absolute: 0,
@@ -174,7 +183,7 @@ fn single_char_integer_literal() {
     assert_eq!(
         Lexer::new("1").next_token().unwrap(),
         Token::new(
-            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 1),
+            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 1, "1"),
             TokenPosition {
                 start: TextPosition {
                     absolute: 0,
@@ -218,7 +227,7 @@ fn identifier() {
     assert_eq!(
         Lexer::new("foo").next_token().unwrap(),
         Token::new(
-            TokenKind::Identifier(String::from("foo")),
+            TokenKind::Identifier("foo"),
             TokenPosition {
                 start: TextPosition {
                     absolute: 0,
@@ -262,7 +271,7 @@ fn string_literal() {
     assert_eq!(
         Lexer::new("\"foo\"").next_token(),
         LexResult::Ok(Token::new(
-            TokenKind::StringLiteral(String::from("foo")),
+            TokenKind::StringLiteral(b"foo".to_vec()),
             TokenPosition::new(
                 TextPosition {
                     absolute: 0,
@@ -270,9 +279,9 @@ fn string_literal() {
                     column: 0,
                 },
                 TextPosition {
-                    absolute: 4,
+                    absolute: 5,
                     line: 0,
-                    column: 4,
+                    column: 5,
                 },
             )
         ))
@@ -288,7 +297,7 @@ fn string_literal_with_spaces() {
             .next_token()
             .unwrap(),
         Token::new(
-            TokenKind::StringLiteral(String::from(contents)),
+            TokenKind::StringLiteral(contents.as_bytes().to_vec()),
             TokenPosition::new(
                 TextPosition {
                     absolute: 0,
@@ -296,9 +305,9 @@ fn string_literal_with_spaces() {
                     column: 0
                 },
                 TextPosition {
-                    absolute: 37,
+                    absolute: 38,
                     line: 0,
-                    column: 37
+                    column: 38
                 },
             ),
         )
@@ -311,7 +320,7 @@ fn empty_string_literal() {
     assert_eq!(
         Lexer::new("\"\"").next_token().unwrap(),
         Token::new(
-            TokenKind::StringLiteral(String::from("")),
+            TokenKind::StringLiteral(Vec::new()),
             TokenPosition {
                 start: TextPosition {
                     absolute: 0,
@@ -319,9 +328,9 @@ fn empty_string_literal() {
                     column: 0
                 },
                 end: TextPosition {
-                    absolute: 1,
+                    absolute: 2,
                     line: 0,
-                    column: 1
+                    column: 2
                 },
             },
         )
@@ -330,17 +339,9 @@ fn empty_string_literal() {
 
 #[test]
 fn keyword_then_identifier() {
-    let mut lexer = Lexer::new("var foo");
-    let mut tokens = Vec::new();
+    let tokens: Vec<Token> = Lexer::new("var foo").map(Result::unwrap).collect();
 
-    loop {
-        match lexer.next_token() {
-            Err(_) => break,
-            Ok(token) => tokens.push(token),
-        }
-    }
-
-    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens.len(), 4);
 
     assert_eq!(
         tokens[0],
@@ -383,7 +384,7 @@ fn keyword_then_identifier() {
     assert_eq!(
         tokens[2],
         Token::new(
-            TokenKind::Identifier(String::from("foo")),
+            TokenKind::Identifier("foo"),
             TokenPosition::new(
                 TextPosition {
                     absolute: 4,
@@ -444,45 +445,100 @@ fn double_character_primitive_token() {
     )
 }
 
+#[test]
+fn ellipsis_token() {
+    let kinds: Vec<TokenKind> = Lexer::new("a.b, ...")
+        .map(|token| token.unwrap().kind)
+        .filter(|kind| !kind.is_trivia())
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Identifier("a"),
+            TokenKind::Dot,
+            TokenKind::Identifier("b"),
+            TokenKind::Comma,
+            TokenKind::Ellipsis,
+            TokenKind::EOF,
+        ]
+    );
+}
+
 #[test]
 fn test_variable_declaration() {
-    let mut lexer = Lexer::new("var foo: u8 = 5;");
-    let mut tokens = Vec::new();
+    let lexer = Lexer::new("var foo: u8 = 5;");
 
     let expected_kinds = vec![
         TokenKind::Keyword(Keyword::Var),
         TokenKind::Whitespace(0),
-        TokenKind::Identifier(String::from("foo")),
+        TokenKind::Identifier("foo"),
         TokenKind::Colon,
         TokenKind::Whitespace(0),
         TokenKind::Keyword(Keyword::U8),
         TokenKind::Whitespace(0),
         TokenKind::Equal,
         TokenKind::Whitespace(0),
-        TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 5),
+        TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 5, "5"),
         TokenKind::SemiColon,
+        TokenKind::EOF,
     ];
 
-    loop {
-        match lexer.next_token() {
-            Ok(token) => tokens.push(token),
-            Err(err) => {
-                println!("{:?}", err);
-                break;
-            }
-        }
-    }
-
-    let actual_kinds: Vec<TokenKind> = tokens.iter().map(|item| item.clone().kind).collect();
+    let actual_kinds: Vec<TokenKind> = lexer.map(|item| item.unwrap().kind).collect();
     assert_eq!(actual_kinds, expected_kinds);
 }
 
 #[test]
-fn unterminated_string_literal() {
-    assert_eq!(
-        Lexer::new("\"foo").next_token().unwrap_err().kind,
-        LexErrorKind::UnexpectedEOF
-    );
+fn unterminated_literals_at_end_of_input() {
+    for (input, quote) in [("\"abc", '"'), ("'a", '\'')] {
+        let error = Lexer::new(input).next_token().unwrap_err();
+
+        assert_eq!(error.kind, LexErrorKind::UnterminatedLiteral);
+        assert_eq!(error.get_msg(), format!("Missing closing `{}`", quote));
+        assert_eq!(error.get_span().start.absolute, 0);
+        assert_eq!(error.get_span().end.absolute, input.len());
+    }
+}
+
+#[test]
+fn unterminated_literals_stop_at_newline() {
+    for input in [
+        "const A: u8 = 'a;\nconst B: u8 = 'b';\nconst C: u8 = 3;",
+        "const A: u8 = \"a;\nconst B: u8 = 'b';\nconst C: u8 = 3;",
+        "const A: u8 = '\\\nconst B: u8 = 'b';\nconst C: u8 = 3;",
+    ] {
+        let results: Vec<LexResult<Token>> = Lexer::new(input).collect();
+        let errors: Vec<&LexError> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+
+        assert_eq!(errors.len(), 1, "{}", input);
+        assert_eq!(errors[0].kind, LexErrorKind::UnterminatedLiteral);
+        assert_eq!(errors[0].get_span().end.line, 0);
+
+        let kinds: Vec<&TokenKind> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .map(|token| &token.kind)
+            .filter(|kind| !kind.is_trivia())
+            .collect();
+
+        assert!(kinds.ends_with(&[
+            &TokenKind::Keyword(Keyword::Const),
+            &TokenKind::Identifier("B"),
+            &TokenKind::Colon,
+            &TokenKind::Keyword(Keyword::U8),
+            &TokenKind::Equal,
+            &TokenKind::CharLiteral(b'b'),
+            &TokenKind::SemiColon,
+            &TokenKind::Keyword(Keyword::Const),
+            &TokenKind::Identifier("C"),
+            &TokenKind::Colon,
+            &TokenKind::Keyword(Keyword::U8),
+            &TokenKind::Equal,
+            &TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 3, "3"),
+            &TokenKind::SemiColon,
+            &TokenKind::EOF,
+        ]));
+    }
 }
 
 #[test]
@@ -495,7 +551,7 @@ fn full_line_comment() {
     assert_eq!(
         lexer.next_token().unwrap(),
         Token::new(
-            TokenKind::Comment(String::from(" this is a comment that should be ignored")),
+            TokenKind::Comment(" this is a comment that should be ignored"),
             TokenPosition::new(
                 TextPosition {
                     absolute: 0,
@@ -523,7 +579,7 @@ fn inline_comment() {
     assert_eq!(
         lexer.next_token().unwrap(),
         Token::new(
-            TokenKind::Comment(String::from("this is 4")),
+            TokenKind::Comment("this is 4"),
             TokenPosition::new(
                 TextPosition {
                     absolute: 2,
@@ -539,3 +595,490 @@ fn inline_comment() {
         )
     )
 }
+
+#[test]
+fn hexadecimal_integer_literal() {
+    assert_eq!(
+        Lexer::new("0xFF_0a").next_token().unwrap(),
+        Token::new(
+            TokenKind::IntegerLiteral(IntegerLiteralKind::Hexadecimal, 0xFF0A, "0xFF_0a"),
+            TokenPosition::new(
+                TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                TextPosition {
+                    absolute: 7,
+                    line: 0,
+                    column: 7,
+                },
+            ),
+        )
+    )
+}
+
+#[test]
+fn binary_integer_literal() {
+    assert_eq!(
+        Lexer::new("0b1010_0101").next_token().unwrap().kind,
+        TokenKind::IntegerLiteral(IntegerLiteralKind::Binary, 0b1010_0101, "0b1010_0101")
+    )
+}
+
+#[test]
+fn integer_literal_prefix_without_digits() {
+    let error = Lexer::new("0x_;").next_token().unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::EmptyIntegerLiteral);
+    assert_eq!(
+        error.get_position(),
+        TextPosition {
+            absolute: 0,
+            line: 0,
+            column: 0,
+        }
+    )
+}
+
+#[test]
+fn integer_literal_with_digits_outside_radix() {
+    for input in ["0b102;", "0x1G;", "12ab;"] {
+        let mut lexer = Lexer::new(input);
+        let error = lexer.next_token().unwrap_err();
+
+        assert_eq!(error.kind, LexErrorKind::NonconformingLexeme);
+        assert_eq!(error.get_span().end.absolute, input.len() - 1);
+        assert_eq!(lexer.next_token().unwrap().kind, TokenKind::SemiColon);
+    }
+}
+
+#[test]
+fn integer_literal_out_of_range() {
+    let error = Lexer::new("70000").next_token().unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::IntegerLiteralOutOfRange);
+    assert_eq!(
+        error.get_span(),
+        TokenPosition::new(
+            TextPosition {
+                absolute: 0,
+                line: 0,
+                column: 0,
+            },
+            TextPosition {
+                absolute: 5,
+                line: 0,
+                column: 5,
+            },
+        )
+    )
+}
+
+#[test]
+fn integer_literal_at_u16_maximum() {
+    assert_eq!(
+        Lexer::new("0xFFFF").next_token().unwrap().kind,
+        TokenKind::IntegerLiteral(IntegerLiteralKind::Hexadecimal, 0xFFFF, "0xFFFF")
+    )
+}
+
+#[test]
+fn wide_integer_literal() {
+    let mut lexer = Lexer::new("70000").with_integer_range(IntegerRange::Wide);
+
+    assert_eq!(
+        lexer.next_token().unwrap().kind,
+        TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 70000, "70000")
+    )
+}
+
+#[test]
+fn wide_integer_literal_out_of_range() {
+    let mut lexer = Lexer::new("0x1_0000_0000").with_integer_range(IntegerRange::Wide);
+
+    assert_eq!(
+        lexer.next_token().unwrap_err().kind,
+        LexErrorKind::IntegerLiteralOutOfRange
+    )
+}
+
+#[test]
+fn escaped_char_literal() {
+    assert_eq!(
+        Lexer::new("'\\n'").next_token().unwrap().kind,
+        TokenKind::CharLiteral(b'\n')
+    )
+}
+
+#[test]
+fn hex_escaped_char_literal() {
+    assert_eq!(
+        Lexer::new("'\\x1B'").next_token().unwrap().kind,
+        TokenKind::CharLiteral(0x1B)
+    )
+}
+
+#[test]
+fn char_literal_followed_by_token() {
+    let mut lexer = Lexer::new("'\\''+");
+
+    assert_eq!(
+        lexer.next_token().unwrap().kind,
+        TokenKind::CharLiteral(b'\'')
+    );
+    assert_eq!(lexer.next_token().unwrap().kind, TokenKind::Plus);
+}
+
+#[test]
+fn string_literal_with_escapes() {
+    assert_eq!(
+        Lexer::new("\"a\\\"b\\t\\\\\"").next_token().unwrap().kind,
+        TokenKind::StringLiteral(b"a\"b\t\\".to_vec())
+    )
+}
+
+#[test]
+fn high_byte_escapes_are_bytes() {
+    assert_eq!(
+        Lexer::new("\"\\xFF\\x80\"").next_token().unwrap().kind,
+        TokenKind::StringLiteral(vec![0xFF, 0x80])
+    );
+    assert_eq!(
+        Lexer::new("'\\xFF'").next_token().unwrap().kind,
+        TokenKind::CharLiteral(0xFF)
+    );
+}
+
+#[test]
+fn non_ascii_characters_in_literals() {
+    for input in ["'Ā'", "\"caf\u{e9}\""] {
+        let mut lexer = Lexer::new(input);
+        let error = lexer.next_token().unwrap_err();
+
+        assert_eq!(error.kind, LexErrorKind::NonAsciiCharacter);
+        assert_eq!(lexer.next_token().unwrap().kind, TokenKind::EOF);
+    }
+}
+
+#[test]
+fn unknown_escape_sequence() {
+    let error = Lexer::new("\"ab\\q\"").next_token().unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnknownEscapeSequence);
+    assert_eq!(
+        error.get_position(),
+        TextPosition {
+            absolute: 3,
+            line: 0,
+            column: 3,
+        }
+    )
+}
+
+#[test]
+fn truncated_hex_escape_sequence() {
+    assert_eq!(
+        Lexer::new("'\\x4'").next_token().unwrap_err().kind,
+        LexErrorKind::UnknownEscapeSequence
+    )
+}
+
+#[test]
+fn recovering_lexer_skips_bad_lexeme() {
+    let mut lexer = Lexer::new("$+");
+
+    assert_eq!(
+        lexer.next_token_recovering(),
+        Token::new(
+            TokenKind::Illegal,
+            TokenPosition::new(
+                TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                TextPosition {
+                    absolute: 1,
+                    line: 0,
+                    column: 1,
+                },
+            ),
+        )
+    );
+    assert_eq!(lexer.next_token_recovering().kind, TokenKind::Plus);
+    assert_eq!(lexer.next_token_recovering().kind, TokenKind::EOF);
+
+    let errors = lexer.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::UnrecognizedCharacter);
+}
+
+#[test]
+fn recovering_lexer_skips_whole_char_literal() {
+    let mut lexer = Lexer::new("'ab';");
+
+    assert_eq!(lexer.next_token_recovering().kind, TokenKind::Illegal);
+    assert_eq!(lexer.next_token_recovering().kind, TokenKind::SemiColon);
+    assert_eq!(
+        lexer.take_errors()[0].kind,
+        LexErrorKind::MultipleCharLiteral
+    );
+}
+
+#[test]
+fn tokenize_reports_every_error() {
+    let (errors, tokens) =
+        tokenize(FileId::default(), "var x: u8 = 70000; $ \"\\q\";").unwrap_err();
+
+    let error_kinds: Vec<LexErrorKind> = errors.into_iter().map(|error| error.kind).collect();
+    assert_eq!(
+        error_kinds,
+        vec![
+            LexErrorKind::IntegerLiteralOutOfRange,
+            LexErrorKind::UnrecognizedCharacter,
+            LexErrorKind::UnknownEscapeSequence,
+        ]
+    );
+
+    let illegal_count = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Illegal)
+        .count();
+    assert_eq!(illegal_count, 3);
+    assert_eq!(tokens[tokens.len() - 2].kind, TokenKind::SemiColon);
+    assert_eq!(tokens[tokens.len() - 1].kind, TokenKind::EOF);
+}
+
+#[test]
+fn lexer_iterator_ends_with_eof() {
+    let mut lexer = Lexer::new("x");
+
+    assert_eq!(
+        lexer.next().unwrap().unwrap().kind,
+        TokenKind::Identifier("x")
+    );
+    assert_eq!(lexer.next().unwrap().unwrap().kind, TokenKind::EOF);
+    assert_eq!(lexer.next(), None);
+}
+
+#[test]
+fn lexer_iterator_continues_past_errors() {
+    let kinds: Vec<Result<TokenKind, LexErrorKind>> = Lexer::new("$ $")
+        .map(|result| result.map(|token| token.kind).map_err(|err| err.kind))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            Err(LexErrorKind::UnrecognizedCharacter),
+            Ok(TokenKind::Whitespace(0)),
+            Err(LexErrorKind::UnrecognizedCharacter),
+            Ok(TokenKind::EOF),
+        ]
+    );
+}
+
+#[test]
+fn lexer_iterator_stops_early() {
+    let identifiers: Vec<TokenKind> = Lexer::new("a b c d")
+        .filter_map(Result::ok)
+        .filter(|token| matches!(token.kind, TokenKind::Identifier(_)))
+        .take(2)
+        .map(|token| token.kind)
+        .collect();
+
+    assert_eq!(
+        identifiers,
+        vec![TokenKind::Identifier("a"), TokenKind::Identifier("b"),]
+    );
+}
+
+#[test]
+fn doc_comment() {
+    assert_eq!(
+        Lexer::new("## Adds two numbers.\nfn").next_token().unwrap(),
+        Token::new(
+            TokenKind::DocComment(" Adds two numbers."),
+            TokenPosition::new(
+                TextPosition {
+                    absolute: 0,
+                    line: 0,
+                    column: 0,
+                },
+                TextPosition {
+                    absolute: 20,
+                    line: 0,
+                    column: 20,
+                },
+            ),
+        )
+    )
+}
+
+#[test]
+fn block_comment() {
+    let kinds: Vec<TokenKind> = Lexer::new("#[ spans\nlines ]#var")
+        .map(|token| token.unwrap().kind)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::BlockComment(" spans\nlines "),
+            TokenKind::Keyword(Keyword::Var),
+            TokenKind::EOF,
+        ]
+    )
+}
+
+#[test]
+fn nested_block_comment() {
+    assert_eq!(
+        Lexer::new("#[ outer #[ inner ]# still outer ]#")
+            .next_token()
+            .unwrap()
+            .kind,
+        TokenKind::BlockComment(" outer #[ inner ]# still outer ")
+    )
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let error = Lexer::new("#[ outer #[ inner ]#").next_token().unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnexpectedEOF);
+    assert_eq!(
+        error.get_position(),
+        TextPosition {
+            absolute: 0,
+            line: 0,
+            column: 0,
+        }
+    )
+}
+
+#[test]
+fn tokenize_attaches_trivia() {
+    let tokens = tokenize(FileId::default(), "## Doc\nconst X: u8 = 5; # five\n").unwrap();
+
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Keyword(Keyword::Const),
+            TokenKind::Identifier("X"),
+            TokenKind::Colon,
+            TokenKind::Keyword(Keyword::U8),
+            TokenKind::Equal,
+            TokenKind::IntegerLiteral(IntegerLiteralKind::Decimal, 5, "5"),
+            TokenKind::SemiColon,
+            TokenKind::EOF,
+        ]
+    );
+
+    let leading: Vec<TokenKind> = tokens[0]
+        .leading_trivia
+        .iter()
+        .map(|trivia| trivia.kind.clone())
+        .collect();
+    assert_eq!(
+        leading,
+        vec![TokenKind::DocComment(" Doc"), TokenKind::Whitespace(1)]
+    );
+
+    let trailing: Vec<TokenKind> = tokens[6]
+        .trailing_trivia
+        .iter()
+        .map(|trivia| trivia.kind.clone())
+        .collect();
+    assert_eq!(
+        trailing,
+        vec![TokenKind::Whitespace(0), TokenKind::Comment(" five")]
+    );
+
+    assert_eq!(tokens[7].leading_trivia[0].kind, TokenKind::Whitespace(1));
+}
+
+#[test]
+fn tokenize_round_trips_source() {
+    let source = "# Header\n\ntype char = u8;\n\nstruct S {  # fields\n    a: char*,\n    #[ b ]# c: u8[0x10],\n}\n\"s\\\"\" 'x'  ";
+    let tokens = tokenize(FileId::default(), source).unwrap();
+
+    let reconstructed: String = tokens.iter().map(|token| token.to_source(source)).collect();
+    assert_eq!(reconstructed, source);
+}
+
+#[test]
+fn column_after_line_break() {
+    let tokens: Vec<Token> = Lexer::new("a\nb").map(Result::unwrap).collect();
+
+    assert_eq!(
+        tokens[2].position.start,
+        TextPosition {
+            absolute: 2,
+            line: 1,
+            column: 0,
+        }
+    )
+}
+
+#[test]
+fn crlf_is_a_single_line_break() {
+    let tokens: Vec<Token> = Lexer::new("a\r\n\r\nb").map(Result::unwrap).collect();
+
+    assert_eq!(tokens[1].kind, TokenKind::Whitespace(2));
+    assert_eq!(
+        tokens[2].position.start,
+        TextPosition {
+            absolute: 5,
+            line: 2,
+            column: 0,
+        }
+    )
+}
+
+#[test]
+fn tab_advances_to_next_tab_stop() {
+    let tokens: Vec<Token> = Lexer::new("ab\tc\t\td").map(Result::unwrap).collect();
+
+    assert_eq!(tokens[2].position.start.column, 4);
+    assert_eq!(tokens[4].position.start.column, 12);
+}
+
+#[test]
+fn configurable_tab_width() {
+    let tokens: Vec<Token> = Lexer::new("\tx")
+        .with_tab_width(8)
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(
+        tokens[1].position.start,
+        TextPosition {
+            absolute: 1,
+            line: 0,
+            column: 8,
+        }
+    )
+}
+
+#[test]
+#[should_panic(expected = "tab width must be at least 1")]
+fn zero_tab_width() {
+    let _ = Lexer::new("\tx").with_tab_width(0);
+}
+
+#[test]
+fn column_counts_characters_not_bytes() {
+    let tokens: Vec<Token> = Lexer::new("#[é]# x").map(Result::unwrap).collect();
+
+    assert_eq!(
+        tokens[2].position.start,
+        TextPosition {
+            absolute: 7,
+            line: 0,
+            column: 6,
+        }
+    )
+}
@@ -1 +1,5 @@
-mod lexer_tests;
\ No newline at end of file
+mod lexer_tests;
+mod parser_tests;
+mod printer_tests;
+mod source_map_tests;
+mod syntax_tests;
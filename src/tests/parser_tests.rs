@@ -0,0 +1,1069 @@
+#[cfg(test)]
+use crate::compiler::*;
+
+#[cfg(test)]
+fn parse_expression(input: &str) -> Expression {
+    let tokens = tokenize(FileId::default(), input).unwrap();
+    let mut parser = Parser::new(FileId::default(), tokens);
+
+    match parser.parse_expression() {
+        Ok((expression, _)) => expression,
+        Err(err) => panic!("failed to parse {:?}: {:?}", input, err),
+    }
+}
+
+// Renders an expression as an S-expression so that tests can assert on its shape.
+#[cfg(test)]
+fn render(expression: &Expression) -> String {
+    match &expression.kind {
+        ExpressionKind::IntegerLiteral(_, value) => value.to_string(),
+        ExpressionKind::CharLiteral(c) => format!("{:?}", *c as char),
+        ExpressionKind::StringLiteral(literal) => {
+            format!("{:?}", String::from_utf8_lossy(literal))
+        }
+        ExpressionKind::Identifier(identifier) => identifier.clone(),
+        ExpressionKind::Error => String::from("error"),
+        ExpressionKind::SizeOf(ty) => format!("(sizeof {})", render_type(ty)),
+        ExpressionKind::Call { callee, arguments } => {
+            let mut rendered = format!("(call {}", render(callee));
+            for argument in arguments {
+                rendered += " ";
+                rendered += &render(argument);
+            }
+            rendered + ")"
+        }
+        ExpressionKind::Index { base, index } => {
+            format!("(index {} {})", render(base), render(index))
+        }
+        ExpressionKind::Member { base, member } => format!("(. {} {})", render(base), member),
+        ExpressionKind::Cast { ty, expression } => {
+            format!("(cast {} {})", render_type(ty), render(expression))
+        }
+        ExpressionKind::ArrayLiteral(elements) => {
+            let elements: Vec<String> = elements.iter().map(render).collect();
+            format!("[{}]", elements.join(" "))
+        }
+        ExpressionKind::ArrayRepeat { value, count } => {
+            format!("[{}; {}]", render(value), render(count))
+        }
+        ExpressionKind::StructLiteral { name, fields } => {
+            let mut rendered = format!("({}", name);
+            for field in fields {
+                rendered += &format!(" ({} {})", field.identifier, render(&field.value));
+            }
+            rendered + ")"
+        }
+        ExpressionKind::Unary(operator, operand) => {
+            format!("({:?} {})", operator, render(operand))
+        }
+        ExpressionKind::Binary(operator, lhs, rhs) => {
+            format!("({:?} {} {})", operator, render(lhs), render(rhs))
+        }
+    }
+}
+
+#[cfg(test)]
+fn render_type(ty: &Type) -> String {
+    match &ty.kind {
+        TypeKind::Named(name) => name.clone(),
+        TypeKind::Pointer(pointee) => format!("(Pointer {})", render_type(pointee)),
+        TypeKind::Array(element, size) => {
+            format!("(Array {} {})", render_type(element), render(size))
+        }
+        TypeKind::Function {
+            parameters,
+            is_variadic,
+            return_type,
+        } => {
+            let mut rendered = String::from("(fn (");
+            let parameters: Vec<String> = parameters.iter().map(render_type).collect();
+            rendered += &parameters.join(" ");
+            if *is_variadic {
+                rendered += " ...";
+            }
+            rendered += ")";
+            if let Some(return_type) = return_type {
+                rendered += " ";
+                rendered += &render_type(return_type);
+            }
+            rendered + ")"
+        }
+        primitive => format!("{:?}", primitive),
+    }
+}
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_eq!(
+        render(&parse_expression("1 + 2 * 3 - 4")),
+        "(Subtract (Add 1 (Multiply 2 3)) 4)"
+    );
+}
+
+#[test]
+fn binary_operators_are_left_associative() {
+    assert_eq!(
+        render(&parse_expression("a - b - c / d / e")),
+        "(Subtract (Subtract a b) (Divide (Divide c d) e))"
+    );
+}
+
+#[test]
+fn c_precedence_levels() {
+    assert_eq!(
+        render(&parse_expression(
+            "a || b && c | d ^ e & f == g < h << i + j * k"
+        )),
+        "(LogicalOr a (LogicalAnd b (BitwiseOr c (BitwiseXor d (BitwiseAnd e (Equal f \
+         (LessThan g (ShiftLeft h (Add i (Multiply j k))))))))))"
+    );
+}
+
+#[test]
+fn parentheses_override_precedence() {
+    assert_eq!(
+        render(&parse_expression("(1 + 2) * 3")),
+        "(Multiply (Add 1 2) 3)"
+    );
+}
+
+#[test]
+fn unary_and_cast_bind_tighter_than_binary() {
+    assert_eq!(
+        render(&parse_expression("-a * !b + <u16> c")),
+        "(Add (Multiply (Negate a) (Not b)) (cast U16 c))"
+    );
+    assert_eq!(
+        render(&parse_expression("*&x")),
+        "(Dereference (AddressOf x))"
+    );
+}
+
+#[test]
+fn casts_next_to_comparisons() {
+    assert_eq!(
+        render(&parse_expression("<u8>x > y")),
+        "(GreaterThan (cast U8 x) y)"
+    );
+    assert_eq!(
+        render(&parse_expression("a < <u8>b")),
+        "(LessThan a (cast U8 b))"
+    );
+    assert_eq!(
+        render(&parse_expression("<u8*><u16*>p")),
+        "(cast (Pointer U8) (cast (Pointer U16) p))"
+    );
+}
+
+#[test]
+fn cast_splits_closing_chevron() {
+    let err = first_error("const A: u8 = <u8*>>p;");
+    assert_eq!(err.msg, "expected expression, found operator `>`");
+
+    assert_eq!(
+        first_error("fn f() { <u8*>>= p; }").msg,
+        "expected expression, found operator `>=`"
+    );
+
+    match &parse_body("<u8>x >>= 1;")[..] {
+        [BodyStatement::Assignment(assignment)] => {
+            assert_eq!(render(&assignment.target), "(cast U8 x)");
+            assert_eq!(assignment.operator, AssignmentOperator::ShiftRightAssign);
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
+
+#[test]
+fn less_than_without_type_is_not_a_cast() {
+    assert_eq!(
+        first_error("const A: u8 = < 1;").msg,
+        "expected expression, found operator `<`"
+    );
+    assert_eq!(
+        first_error("const A: u8 = <u8 x;").msg,
+        "expected `*`, `[` or `>`, found identifier `x`"
+    );
+}
+
+#[test]
+fn as_casts() {
+    assert_eq!(render(&parse_expression("x as u16")), "(cast U16 x)");
+    assert_eq!(
+        render(&parse_expression("-x as u8 + y")),
+        "(Add (cast U8 (Negate x)) y)"
+    );
+    assert_eq!(
+        render(&parse_expression("a * b as i16 as u8")),
+        "(Multiply a (cast U8 (cast I16 b)))"
+    );
+    assert_eq!(
+        render(&parse_expression("p as u8** == q")),
+        "(Equal (cast (Pointer (Pointer U8)) p) q)"
+    );
+    assert_eq!(
+        render(&parse_expression("x as u8 * y")),
+        "(Multiply (cast U8 x) y)"
+    );
+    assert_eq!(
+        render(&parse_expression("(x as u8*) * y")),
+        "(Multiply (cast (Pointer U8) x) y)"
+    );
+    assert_eq!(
+        render(&parse_expression("(p as u8*) - 1")),
+        "(Subtract (cast (Pointer U8) p) 1)"
+    );
+    assert_eq!(
+        render(&parse_expression("x as u8 * !y")),
+        "(Multiply (cast U8 x) (Not y))"
+    );
+    assert_eq!(
+        render(&parse_expression("x as u8 * (-y)")),
+        "(Multiply (cast U8 x) (Negate y))"
+    );
+    assert_eq!(
+        render(&parse_expression("x as fn(): u8 * z")),
+        "(Multiply (cast (fn () U8) x) z)"
+    );
+    assert_eq!(
+        render(&parse_expression("x as u8 > y")),
+        "(GreaterThan (cast U8 x) y)"
+    );
+}
+
+#[test]
+fn ambiguous_as_pointer_casts() {
+    let cases = [
+        ("x as u8 * -y", "operator `-`"),
+        ("x as u8 * &y", "operator `&`"),
+        ("x as u8 * *p", "operator `*`"),
+        ("x as u8* * (y)", "operator `*`"),
+        ("p as u8* + 1", "operator `+`"),
+        ("p as u8* - 1", "operator `-`"),
+        ("x as u8*[4] + y", "`[`"),
+        ("p as u8* < q", "operator `<`"),
+    ];
+
+    for (input, found) in cases {
+        let err = first_error(&format!("const A: u8 = {};", input));
+
+        assert!(
+            matches!(err.kind, ParseErrorKind::AmbiguousCast),
+            "{}",
+            input
+        );
+        assert_eq!(
+            err.msg,
+            format!(
+                "`*` after a cast type followed by {} is ambiguous; parenthesize the cast or the \
+                 multiplication",
+                found
+            )
+        );
+    }
+
+    let input = "const A: u8 = p as u8* - 1;";
+    assert_eq!(snippet(input, first_error(input).span), "* -");
+}
+
+#[test]
+fn postfix_binds_tightest() {
+    assert_eq!(
+        render(&parse_expression("-leds[i + 1].state")),
+        "(Negate (. (index leds (Add i 1)) state))"
+    );
+    assert_eq!(
+        render(&parse_expression("write(port, 'x', \"hi\",)(0)")),
+        "(call (call write port 'x' \"hi\") 0)"
+    );
+    assert_eq!(render(&parse_expression("f()")), "(call f)");
+}
+
+#[test]
+fn sizeof_takes_a_type() {
+    assert_eq!(
+        render(&parse_expression("sizeof(u16) * 2")),
+        "(Multiply (sizeof U16) 2)"
+    );
+}
+
+#[test]
+fn expression_spans_cover_their_source() {
+    let source = "foo(1) + (2 * 3)";
+    let expression = parse_expression(source);
+
+    assert_eq!(expression.span, Span::new(FileId::default(), 0, 16));
+
+    match expression.kind {
+        ExpressionKind::Binary(_, lhs, rhs) => {
+            assert_eq!(lhs.span, Span::new(FileId::default(), 0, 6));
+            assert_eq!(rhs.span, Span::new(FileId::default(), 9, 16));
+        }
+        kind => panic!("expected a binary expression, got {:?}", kind),
+    }
+}
+
+#[test]
+fn unterminated_call_is_an_error() {
+    let tokens = tokenize(FileId::default(), "foo(1, 2").unwrap();
+    let mut parser = Parser::new(FileId::default(), tokens);
+
+    assert!(parser.parse_expression().is_err());
+}
+
+#[cfg(test)]
+fn parse_function(input: &str) -> FunctionDeclaration {
+    match parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    ) {
+        Ok(mut statements) => match statements.remove(0) {
+            Statement::FunctionDeclaration(function) => function,
+            statement => panic!("expected a function, got {:?}", statement),
+        },
+        Err((err, _)) => panic!("failed to parse {:?}: {:?}", input, err),
+    }
+}
+
+#[test]
+fn function_prototype() {
+    let function = parse_function("fn strlen(s: u16): u16;");
+
+    assert_eq!(function.linkage, Linkage::Internal);
+    assert_eq!(function.identifier, "strlen");
+    assert_eq!(function.parameters.len(), 1);
+    assert_eq!(function.parameters[0].identifier, "s");
+    assert_eq!(function.parameters[0].field_type.kind, TypeKind::U16);
+    assert!(!function.is_variadic);
+    assert_eq!(function.return_type.map(|ty| ty.kind), Some(TypeKind::U16));
+    assert!(function.body.is_none());
+}
+
+#[test]
+fn extern_variadic_function() {
+    let function = parse_function("extern fn printf(format: u16, ...): i16;");
+
+    assert_eq!(function.linkage, Linkage::External);
+    assert_eq!(function.parameters.len(), 1);
+    assert!(function.is_variadic);
+    assert_eq!(function.return_type.map(|ty| ty.kind), Some(TypeKind::I16));
+}
+
+#[test]
+fn function_definition_without_return_type() {
+    let function = parse_function("fn init(a: u8, b: i8,) { var x: u8 = a; const Y: i8 = b; }");
+
+    assert_eq!(function.parameters.len(), 2);
+    assert_eq!(function.return_type, None);
+    assert_eq!(function.body.map(|body| body.len()), Some(2));
+
+    assert_eq!(
+        parse_function("fn halt() {}").body.map(|body| body.len()),
+        Some(0)
+    );
+}
+
+#[test]
+fn invalid_function_declarations() {
+    for input in [
+        "extern fn puts(s: u16) {}",
+        "fn f(...);",
+        "fn f(a: u8, ..., b: u8);",
+        "fn f(a: u8)",
+    ] {
+        assert!(
+            parse(
+                FileId::default(),
+                tokenize(FileId::default(), input).unwrap()
+            )
+            .is_err(),
+            "{:?} should not parse",
+            input
+        );
+    }
+}
+
+#[cfg(test)]
+fn parse_body(input: &str) -> Vec<BodyStatement> {
+    let source = format!("fn f() {{ {} }}", input);
+
+    parse_function(&source).body.unwrap()
+}
+
+#[test]
+fn assignment_statements() {
+    let body = parse_body("x = 1; leds[i].state <<= 2; *p ^= mask;");
+
+    let operators: Vec<AssignmentOperator> = body
+        .iter()
+        .map(|statement| match statement {
+            BodyStatement::Assignment(assignment) => assignment.operator,
+            statement => panic!("expected an assignment, got {:?}", statement),
+        })
+        .collect();
+
+    assert_eq!(
+        operators,
+        vec![
+            AssignmentOperator::Assign,
+            AssignmentOperator::ShiftLeftAssign,
+            AssignmentOperator::BitwiseXorAssign,
+        ]
+    );
+
+    match &body[1] {
+        BodyStatement::Assignment(assignment) => {
+            assert_eq!(render(&assignment.target), "(. (index leds i) state)")
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn if_else_and_while() {
+    let body = parse_body("if a == 1 { x = 2; } else { while x { x -= 1; } } if b { }");
+
+    match &body[0] {
+        BodyStatement::If(statement) => {
+            assert_eq!(render(&statement.condition), "(Equal a 1)");
+            assert_eq!(statement.body.len(), 1);
+
+            match statement.else_body.as_deref() {
+                Some([BodyStatement::While(inner)]) => assert_eq!(inner.body.len(), 1),
+                else_body => panic!("expected a while loop, got {:?}", else_body),
+            }
+        }
+        statement => panic!("expected an if statement, got {:?}", statement),
+    }
+
+    match &body[1] {
+        BodyStatement::If(statement) => assert!(statement.else_body.is_none()),
+        statement => panic!("expected an if statement, got {:?}", statement),
+    }
+}
+
+#[test]
+fn for_loops_with_and_without_step() {
+    let body = parse_body("for i: u8 = 0 : 10 { } for j: i16 = 10 : 0 : -1 { continue; }");
+
+    match &body[..] {
+        [BodyStatement::For(first), BodyStatement::For(second)] => {
+            assert_eq!(first.variable.identifier, "i");
+            assert_eq!(render(&first.end), "10");
+            assert!(first.step.is_none());
+
+            assert_eq!(second.variable.field_type.kind, TypeKind::I16);
+            assert_eq!(
+                second.step.as_ref().map(render),
+                Some(String::from("(Negate 1)"))
+            );
+            assert!(matches!(second.body[..], [BodyStatement::Continue(_)]));
+        }
+        body => panic!("expected two for loops, got {:?}", body),
+    }
+}
+
+#[test]
+fn switch_with_cases_and_default() {
+    let body = parse_body("switch c { case 'a': x = 1; break; case 'b': default: return; }");
+
+    match &body[..] {
+        [BodyStatement::Switch(switch)] => {
+            assert_eq!(render(&switch.value), "c");
+            assert_eq!(switch.cases.len(), 3);
+            assert_eq!(switch.cases[0].body.len(), 2);
+            assert!(switch.cases[1].body.is_empty());
+            assert!(switch.cases[2].value.is_none());
+            assert!(matches!(
+                switch.cases[2].body[..],
+                [BodyStatement::Return(None, _)]
+            ));
+        }
+        body => panic!("expected a switch, got {:?}", body),
+    }
+}
+
+#[test]
+fn local_declarations_and_return() {
+    let body = parse_body("type t = u8; const A: u8 = 1; var b: u8 = A; return b + 1;");
+
+    assert!(matches!(
+        body[..],
+        [
+            BodyStatement::TypeDeclaration(_),
+            BodyStatement::ConstantDeclaration(_),
+            BodyStatement::VariableDeclaration(_),
+            BodyStatement::Return(Some(_), _),
+        ]
+    ));
+}
+
+#[test]
+fn expression_without_assignment_is_not_a_statement() {
+    let input = "fn f() { x + 1; }";
+
+    assert!(parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap()
+    )
+    .is_err());
+}
+
+#[cfg(test)]
+fn parse_parameter_types(parameters: &str) -> Vec<String> {
+    parse_function(&format!("fn f({});", parameters))
+        .parameters
+        .iter()
+        .map(|parameter| render_type(&parameter.field_type))
+        .collect()
+}
+
+#[test]
+fn named_and_pointer_types() {
+    assert_eq!(
+        parse_parameter_types("a: char, b: char**"),
+        vec!["char", "(Pointer (Pointer char))"]
+    );
+}
+
+#[test]
+fn postfix_type_operators_apply_left_to_right() {
+    assert_eq!(
+        parse_parameter_types("a: u8*[4], b: u8[4]*"),
+        vec!["(Array (Pointer U8) 4)", "(Pointer (Array U8 4))"]
+    );
+}
+
+#[test]
+fn array_sizes_are_expressions() {
+    assert_eq!(
+        parse_parameter_types("a: u8[SIZE * 2]"),
+        vec!["(Array U8 (Multiply SIZE 2))"]
+    );
+}
+
+#[test]
+fn function_types() {
+    assert_eq!(
+        parse_parameter_types("f: fn(), g: fn(u8, ...): i8*"),
+        vec!["(fn ())", "(fn (U8 ...) (Pointer I8))"]
+    );
+}
+
+#[test]
+fn readme_example_parses() {
+    let input = "
+type char = u8;
+
+const SOME_CONST: u8 = 2;
+
+fn some_function(arg: u8): u8 {
+    var val: u8 = arg + SOME_CONST;
+    return val + 1;
+}
+
+struct SomeStruct {
+    field1: s16,
+    field2: char*,
+    field3: u8[10],
+}";
+
+    let statements = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(statements.len(), 4);
+}
+
+#[cfg(test)]
+fn parse_enum(input: &str) -> EnumDeclaration {
+    match parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    ) {
+        Ok(mut statements) => match statements.remove(0) {
+            Statement::EnumDeclaration(declaration) => declaration,
+            statement => panic!("expected an enum, got {:?}", statement),
+        },
+        Err((err, _)) => panic!("failed to parse {:?}: {:?}", input, err),
+    }
+}
+
+#[test]
+fn enum_with_explicit_discriminants() {
+    let declaration = parse_enum("enum Pin { Led = 0x10, Button, Buzzer = 1 << 5, }");
+    let variants = declaration.variants.unwrap();
+
+    assert_eq!(declaration.identifier, "Pin");
+    assert_eq!(
+        variants
+            .iter()
+            .map(|variant| variant.identifier.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Led", "Button", "Buzzer"]
+    );
+    assert_eq!(
+        variants[0].value.as_ref().map(render),
+        Some(String::from("16"))
+    );
+    assert!(variants[1].value.is_none());
+    assert_eq!(
+        variants[2].value.as_ref().map(render),
+        Some(String::from("(ShiftLeft 1 5)"))
+    );
+}
+
+#[test]
+fn opaque_enum() {
+    let declaration = parse_enum("enum Mode;");
+
+    assert_eq!(declaration.identifier, "Mode");
+    assert!(declaration.variants.is_none());
+    assert_eq!(
+        parse_enum("enum Empty {}").variants.map(|v| v.len()),
+        Some(0)
+    );
+}
+
+#[test]
+fn opaque_struct_and_union() {
+    let input = "struct Device; union Register; struct Pair { a: u8, b: Device*, }";
+
+    match &parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap()[..]
+    {
+        [Statement::StructDeclaration(device), Statement::UnionDeclaration(register), Statement::StructDeclaration(pair)] =>
+        {
+            assert_eq!(device.identifier, "Device");
+            assert!(device.fields.is_none());
+            assert!(register.is_union);
+            assert!(register.fields.is_none());
+            assert_eq!(pair.fields.as_ref().map(|fields| fields.len()), Some(2));
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
+
+#[test]
+fn last_field_comma_is_optional() {
+    let input = "struct S { a: u8 } union U { a: u8, b: i16 }";
+    let statements = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(statements.len(), 2);
+    assert_eq!(
+        first_error("struct S { a: u8 b: u8 }").msg,
+        "expected `*`, `[`, `,` or `}`, found identifier `b`"
+    );
+}
+
+#[test]
+fn parser_skips_raw_trivia_tokens() {
+    let input = "# Configuration\nconst X: u8 = 5; #[ block ]# var y: u8 = X;\n";
+    let raw: Vec<Token> = Lexer::new(input).map(Result::unwrap).collect();
+
+    assert!(raw.iter().any(|token| token.kind.is_trivia()));
+    assert_eq!(parse(FileId::default(), raw).unwrap().len(), 2);
+}
+
+#[test]
+fn token_input_exposes_skipped_trivia() {
+    let input = "a # note\n  b";
+
+    let raw = TokenInput::new(Lexer::new(input).map(Result::unwrap).collect());
+    let attached = TokenInput::new(tokenize(FileId::default(), input).unwrap());
+
+    for mut tokens in [raw, attached] {
+        assert_eq!(tokens.next().unwrap().kind, TokenKind::Identifier("a"));
+
+        let trivia: Vec<TokenKind> = tokens
+            .leading_trivia()
+            .into_iter()
+            .map(|trivia| trivia.kind)
+            .collect();
+        let comment = trivia.contains(&TokenKind::Comment(" note"));
+
+        assert_eq!(tokens.current().unwrap().kind, TokenKind::Identifier("b"));
+        assert_eq!(tokens.prev().unwrap().kind, TokenKind::Identifier("a"));
+
+        // Once attached, the comment trails `a` rather than leading `b`.
+        assert_eq!(comment, tokens.prev().unwrap().trailing_trivia.is_empty());
+    }
+}
+
+#[test]
+fn recovery_reports_every_declaration_error() {
+    let input = "const A: u8 = ;\nstruct S { a: u8, }\nvar b u8 = 1;\nfn f() {}";
+    let (errors, statements) = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        statements[..],
+        [
+            Statement::Error(_),
+            Statement::StructDeclaration(_),
+            Statement::Error(_),
+            Statement::FunctionDeclaration(_),
+        ]
+    ));
+
+    match statements[2] {
+        Statement::Error(span) => assert_eq!(
+            &input[span.start as usize..span.end as usize],
+            "var b u8 = 1;"
+        ),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn recovery_inside_function_bodies() {
+    let input = "fn f() { x + 1; y = 2; if { } return y; }\nfn g(): u8 { return 1 }";
+    let (errors, statements) = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 3);
+
+    match &statements[..] {
+        [Statement::FunctionDeclaration(f), Statement::FunctionDeclaration(g)] => {
+            assert!(matches!(
+                f.body.as_deref(),
+                Some([
+                    BodyStatement::Error(_),
+                    BodyStatement::Assignment(_),
+                    BodyStatement::Error(_),
+                    BodyStatement::Return(Some(_), _),
+                ])
+            ));
+            assert!(matches!(g.body.as_deref(), Some([BodyStatement::Error(_)])));
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
+
+#[test]
+fn recovery_skips_stray_closing_braces() {
+    let input = "} } const A: u8 = 1;";
+    let (errors, statements) = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        statements.last(),
+        Some(Statement::ConstantDeclaration(_))
+    ));
+}
+
+#[cfg(test)]
+fn first_error(input: &str) -> ParseError {
+    match parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    ) {
+        Err((mut errors, _)) => match errors.remove(0) {
+            CompilerError::ParseError(err) => err,
+            err => panic!("expected a parse error, got {:?}", err),
+        },
+        Ok(statements) => panic!("{:?} should not parse, got {:?}", input, statements),
+    }
+}
+
+#[test]
+fn error_lists_expected_tokens() {
+    let err = first_error("struct S { a u8, }");
+
+    assert_eq!(err.msg, "expected `:`, found `u8`");
+    assert_eq!(err.expected, vec![Expected::Token(TokenKind::Colon)]);
+    assert_eq!(err.span, Span::new(FileId::default(), 13, 15));
+}
+
+#[test]
+fn error_accumulates_alternatives() {
+    assert_eq!(
+        first_error("const A: u8 1;").msg,
+        "expected `*`, `[` or `=`, found integer literal `1`"
+    );
+    assert_eq!(
+        first_error("var a: u8 1;").msg,
+        "expected `*`, `[`, `;` or `=`, found integer literal `1`"
+    );
+    assert_eq!(
+        first_error("x = 1;").msg,
+        "expected `const`, `var`, `extern`, `fn`, `struct`, `union`, `enum` or `type`, found \
+         identifier `x`"
+    );
+}
+
+#[test]
+fn list_errors_include_closing_delimiter() {
+    let cases = [
+        (
+            "fn f(a: u8 b: u8) {}",
+            "expected `*`, `[`, `,` or `)`, found identifier `b`",
+        ),
+        (
+            "enum E { A B }",
+            "expected `=`, `,` or `}`, found identifier `B`",
+        ),
+        (
+            "const A: u8 = f(1 2);",
+            "expected `,` or `)`, found integer literal `2`",
+        ),
+        (
+            "const A: u8[2] = [1 2];",
+            "expected `;`, `,` or `]`, found integer literal `2`",
+        ),
+        (
+            "const A: P = P { x: 1 y: 2 };",
+            "expected `,` or `}`, found identifier `y`",
+        ),
+        (
+            "type F = fn(u8 u8);",
+            "expected `*`, `[`, `,` or `)`, found `u8`",
+        ),
+    ];
+
+    for (input, msg) in cases {
+        assert_eq!(first_error(input).msg, msg, "{}", input);
+    }
+}
+
+#[test]
+fn error_names_productions() {
+    assert_eq!(first_error("const A: = 1;").msg, "expected type, found `=`");
+    assert_eq!(
+        first_error("type A = *u8;").msg,
+        "expected type, found operator `*`"
+    );
+    assert_eq!(
+        first_error("fn f() { return + ; }").msg,
+        "expected expression, found `;`"
+    );
+    assert_eq!(
+        first_error("fn f() { x 1; }").msg,
+        "expected assignment operator, found integer literal `1`"
+    );
+}
+
+#[test]
+fn recovery_consumes_the_failed_body() {
+    let inputs = [
+        "struct S { a u8, b: u8, }",
+        "enum E { A B }",
+        "const A: u8 = P { x: 1 y: 2 };",
+        "fn f() { switch x { x = 1; } }",
+    ];
+
+    for input in inputs {
+        let (errors, _) = parse(
+            FileId::default(),
+            tokenize(FileId::default(), input).unwrap(),
+        )
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{}: {:?}", input, errors);
+    }
+}
+
+#[test]
+fn error_at_end_of_file() {
+    let err = first_error("fn f() {");
+
+    assert!(matches!(err.kind, ParseErrorKind::UnexpectedEOF));
+    assert_eq!(err.msg, "expected `}` or statement, found end of file");
+    assert_eq!(err.span, Span::new(FileId::default(), 8, 8));
+}
+
+#[cfg(test)]
+fn snippet(input: &str, span: Span) -> &str {
+    &input[span.start as usize..span.end as usize]
+}
+
+#[test]
+fn declarations_and_fields_have_spans() {
+    let input = "# Pins\nconst LED: u8* = 0x10;\nstruct S { a: u8[4], }\nenum E { A = 1, B }";
+    let statements = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        snippet(input, statements[0].span()),
+        "const LED: u8* = 0x10;"
+    );
+    assert_eq!(
+        snippet(input, statements[1].span()),
+        "struct S { a: u8[4], }"
+    );
+    assert_eq!(snippet(input, statements[2].span()), "enum E { A = 1, B }");
+
+    match &statements[..] {
+        [Statement::ConstantDeclaration(constant), Statement::StructDeclaration(s), Statement::EnumDeclaration(e)] =>
+        {
+            assert_eq!(snippet(input, constant.const_type.span), "u8*");
+
+            let field = &s.fields.as_ref().unwrap()[0];
+            assert_eq!(snippet(input, field.span), "a: u8[4]");
+            assert_eq!(snippet(input, field.field_type.span), "u8[4]");
+
+            let variants = e.variants.as_ref().unwrap();
+            assert_eq!(snippet(input, variants[0].span), "A = 1");
+            assert_eq!(snippet(input, variants[1].span), "B");
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
+
+#[test]
+fn statements_have_spans() {
+    let input = "fn f(): u8 {\n    if x { break; } else { x += 1; }\n    return x;\n}";
+    let function = parse_function(input);
+    let body = function.body.as_ref().unwrap();
+
+    assert_eq!(snippet(input, function.span), input);
+    assert_eq!(
+        snippet(input, body[0].span()),
+        "if x { break; } else { x += 1; }"
+    );
+    assert_eq!(snippet(input, body[1].span()), "return x;");
+
+    match &body[0] {
+        BodyStatement::If(statement) => {
+            assert_eq!(snippet(input, statement.body[0].span()), "break;");
+            assert_eq!(
+                snippet(input, statement.else_body.as_ref().unwrap()[0].span()),
+                "x += 1;"
+            );
+        }
+        statement => panic!("expected an if statement, got {:?}", statement),
+    }
+}
+
+#[test]
+fn uninitialized_variables() {
+    let input = "var buffer: u8[16]; var count: u8 = 0;";
+
+    match &parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap()[..]
+    {
+        [Statement::VariableDeclaration(buffer), Statement::VariableDeclaration(count)] => {
+            assert!(buffer.value.is_none());
+            assert_eq!(count.value.as_ref().map(render), Some(String::from("0")));
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+
+    let body = parse_body("var i: u8; i = 1;");
+    assert!(matches!(&body[0], BodyStatement::VariableDeclaration(i) if i.value.is_none()));
+}
+
+#[test]
+fn constant_without_value_is_an_error() {
+    let input = "const LIMIT: u8; const OK: u8 = 1;";
+    let (errors, statements) = parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        statements[..],
+        [Statement::Error(_), Statement::ConstantDeclaration(_)]
+    ));
+
+    match &errors[0] {
+        CompilerError::ParseError(err) => {
+            assert!(matches!(err.kind, ParseErrorKind::UninitializedConstant));
+            assert_eq!(err.msg, "constant `LIMIT` must be given a value");
+            assert_eq!(snippet(input, err.span), "const LIMIT: u8");
+        }
+        err => panic!("expected a parse error, got {:?}", err),
+    }
+}
+
+#[test]
+fn array_literals() {
+    assert_eq!(render(&parse_expression("[1, 2, 3]")), "[1 2 3]");
+    assert_eq!(render(&parse_expression("[1, 2,]")), "[1 2]");
+    assert_eq!(render(&parse_expression("[]")), "[]");
+    assert_eq!(render(&parse_expression("[0; 16]")), "[0; 16]");
+    assert_eq!(
+        render(&parse_expression("[[1, 2], [0; N * 2]][i]")),
+        "(index [[1 2] [0; (Multiply N 2)]] i)"
+    );
+}
+
+#[test]
+fn struct_literals() {
+    assert_eq!(
+        render(&parse_expression("Point { x: 1, y: a + 2 }")),
+        "(Point (x 1) (y (Add a 2)))"
+    );
+    assert_eq!(
+        render(&parse_expression(
+            "Line { from: Point { x: 0, y: 0, }, to: origin }"
+        )),
+        "(Line (from (Point (x 0) (y 0))) (to origin))"
+    );
+    assert_eq!(render(&parse_expression("Empty {}")), "(Empty)");
+}
+
+#[test]
+fn struct_literals_in_conditions_need_parentheses() {
+    match &parse_body("if x { return; } while (p == Point { x: 1, y: 2 }) { }")[..] {
+        [BodyStatement::If(if_statement), BodyStatement::While(while_loop)] => {
+            assert_eq!(render(&if_statement.condition), "x");
+            assert_eq!(if_statement.body.len(), 1);
+            assert_eq!(
+                render(&while_loop.condition),
+                "(Equal p (Point (x 1) (y 2)))"
+            );
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
+
+#[test]
+fn rom_table_initializers() {
+    let input = r#"
+const FONT: u8[4] = [0x00, 0x18, 0x3C, 0x7E];
+const GREETING: u8[5] = "hello";
+"#;
+
+    match &parse(
+        FileId::default(),
+        tokenize(FileId::default(), input).unwrap(),
+    )
+    .unwrap()[..]
+    {
+        [Statement::ConstantDeclaration(font), Statement::ConstantDeclaration(greeting)] => {
+            assert_eq!(render(font.value.as_ref().unwrap()), "[0 24 60 126]");
+            assert_eq!(render(greeting.value.as_ref().unwrap()), "\"hello\"");
+            assert_eq!(render_type(&greeting.const_type), "(Array U8 5)");
+        }
+        statements => panic!("unexpected statements {:?}", statements),
+    }
+}
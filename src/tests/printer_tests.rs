@@ -0,0 +1,78 @@
+#[cfg(test)]
+use crate::compiler::*;
+
+#[cfg(test)]
+fn parse_expression(input: &str) -> Expression {
+    let tokens = tokenize(FileId::default(), input).unwrap();
+    let mut parser = Parser::new(FileId::default(), tokens);
+
+    match parser.parse_expression() {
+        Ok((expression, _)) => expression,
+        Err(err) => panic!("failed to parse {:?}: {:?}", input, err),
+    }
+}
+
+#[cfg(test)]
+fn reprint(input: &str) -> String {
+    parse_expression(input).to_string()
+}
+
+#[test]
+fn prints_only_needed_parentheses() {
+    assert_eq!(reprint("((a + b)) * c"), "(a + b) * c");
+    assert_eq!(reprint("a + (b * c)"), "a + b * c");
+    assert_eq!(reprint("(a - b) - c"), "a - b - c");
+    assert_eq!(reprint("a - (b - c)"), "a - (b - c)");
+    assert_eq!(reprint("(a || b) && c"), "(a || b) && c");
+    assert_eq!(reprint("-(a + 1)"), "-(a + 1)");
+    assert_eq!(reprint("(-a)[1]"), "(-a)[1]");
+    assert_eq!(reprint("(*p).field"), "(*p).field");
+    assert_eq!(reprint("<u8>(a + b)"), "<u8>(a + b)");
+    assert_eq!(reprint("& &x"), "& &x");
+}
+
+#[test]
+fn prints_literals_and_postfix_expressions() {
+    assert_eq!(reprint("0x1f + 0b101 + 10"), "0x1F + 0b101 + 10");
+    assert_eq!(reprint(r#"f('\n', "a\"b", )"#), r#"f('\n', "a\"b")"#);
+    assert_eq!(reprint("[1, 2,][0]"), "[1, 2][0]");
+    assert_eq!(reprint("[0;16]"), "[0; 16]");
+    assert_eq!(reprint("Point{x:1,y:2}.x"), "(Point { x: 1, y: 2 }).x");
+    assert_eq!(reprint("(Point { x: 1 })[0]"), "(Point { x: 1 })[0]");
+    assert_eq!(reprint("Point { x: 1 }"), "Point { x: 1 }");
+    assert_eq!(reprint("sizeof(u8*[4])"), "sizeof(u8*[4])");
+    assert_eq!(
+        reprint("<fn(u8, ...): i16*>callback"),
+        "<fn(u8, ...): i16*>callback"
+    );
+}
+
+#[test]
+fn printing_round_trips() {
+    let inputs = [
+        "a * (b + c) - d / e % f",
+        "a << 1 >> (2 << b)",
+        "a < b == (c != d) >= e",
+        "a & (b ^ c) | d && !(e || f)",
+        "*p[i] + &s.f - -x",
+        "<i16>-<u8>x * f(a, b)(c)",
+        "(Point { x: 1 }).x",
+        "'\\xFF' + '\\'' + '\"'",
+        "\"\\x00\\x80\\xFF\\n'\"",
+    ];
+
+    for input in inputs {
+        assert_eq!(reprint(input), input);
+    }
+}
+
+#[test]
+fn operators_print_from_the_table() {
+    assert_eq!(BinaryOperator::ShiftLeft.to_string(), "<<");
+    assert_eq!(UnaryOperator::Not.to_string(), "!");
+    assert_eq!(AssignmentOperator::BitwiseXorAssign.to_string(), "^=");
+
+    for info in OPERATORS {
+        assert!(std::ptr::eq(OperatorInfo::of(info.operator), info));
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+use crate::compiler::*;
+
+#[test]
+fn spans_resolve_to_their_own_file() {
+    let mut source_map = SourceMap::new();
+    let main = source_map.add_file("main.opal", String::from("const X: u8 = 1;\n"));
+    let driver = source_map.add_file("driver.opal", String::from("# LED driver\nfn blink();\n"));
+
+    let span = Span::new(driver, 16, 21);
+
+    assert_eq!(source_map.name(main), "main.opal");
+    assert_eq!(source_map.snippet(span), "blink");
+    assert_eq!(
+        source_map.location(span),
+        Location {
+            file_name: "driver.opal",
+            line: 1,
+            column: 3,
+        }
+    );
+    assert_eq!(source_map.location(span).to_string(), "driver.opal:2:4");
+}
+
+#[test]
+fn line_of_span() {
+    let mut source_map = SourceMap::new();
+    let file = source_map.add_file("a.opal", String::from("type a = u8;\r\ntype b = i8;\r\n"));
+
+    assert_eq!(source_map.line(Span::new(file, 19, 20)), "type b = i8;");
+}
+
+#[test]
+fn location_at_end_of_file() {
+    let mut source_map = SourceMap::new();
+    let file = source_map.add_file("a.opal", String::from("x\n"));
+
+    assert_eq!(
+        source_map.location(Span::new(file, 2, 2)),
+        Location {
+            file_name: "a.opal",
+            line: 1,
+            column: 0,
+        }
+    );
+}
+
+#[test]
+fn token_position_to_span() {
+    let mut source_map = SourceMap::new();
+    let source = "var foo: u8;";
+    let file = source_map.add_file("a.opal", String::from(source));
+
+    let tokens = tokenize(file, source).unwrap();
+    let span = tokens[1].position.to_span(file);
+
+    assert_eq!(source_map.snippet(span), "foo");
+    assert_eq!(span.to(tokens[3].position.to_span(file)).len(), 7);
+}
+
+#[test]
+fn parse_error_span_points_into_file() {
+    let mut source_map = SourceMap::new();
+    let file = source_map.add_file("a.opal", String::from("type x"));
+
+    let tokens = tokenize(file, source_map.source(file)).unwrap();
+    let (errors, _) = parse(file, tokens).unwrap_err();
+
+    match &errors[0] {
+        CompilerError::ParseError(error) => assert_eq!(error.span.file, file),
+        CompilerError::LexError(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn lex_error_span_points_into_file() {
+    let mut source_map = SourceMap::new();
+    source_map.add_file("a.opal", String::from("var a: u8;"));
+    let file = source_map.add_file("b.opal", String::from("var b: u8 = $;"));
+
+    let (errors, _) = tokenize(file, source_map.source(file)).unwrap_err();
+
+    assert_eq!(errors[0].span().file, file);
+    assert_eq!(errors[0].span().start, 12);
+}
+
+#[test]
+fn location_agrees_with_lexer() {
+    let source = "\tconst\r\n  X:\tu8 = 1; #[é]#\r\n";
+    let mut source_map = SourceMap::new().with_tab_width(8);
+    let file = source_map.add_file("a.opal", String::from(source));
+
+    for token in Lexer::new(source).with_tab_width(8).map(Result::unwrap) {
+        let location = source_map.location(token.position.to_span(file));
+
+        assert_eq!(location.line, token.position.start.line);
+        assert_eq!(location.column, token.position.start.column);
+    }
+}
+
+#[test]
+#[should_panic(expected = "tab width must be at least 1")]
+fn zero_tab_width() {
+    let _ = SourceMap::new().with_tab_width(0);
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+use crate::compiler::ast::{self, AstNode};
+#[cfg(test)]
+use crate::compiler::*;
+
+#[cfg(test)]
+const SOURCE: &str = "\
+## Blinks an LED.
+fn blink(led: u8*, times: u8): u8 {
+    # Count down.
+    while (times > 0) {
+        *led = !*led ; times -= 1;
+    }
+    return ( times + 1 ) * 2;
+}
+
+struct Point { x: u8, y: u8, }
+";
+
+#[cfg(test)]
+fn find(root: &SyntaxNode, kind: SyntaxKind) -> SyntaxNode {
+    match root
+        .descendants()
+        .into_iter()
+        .find(|node| node.kind() == kind)
+    {
+        Some(node) => node,
+        None => panic!("no {:?} node in {:?}", kind, root.text()),
+    }
+}
+
+#[test]
+fn syntax_tree_is_lossless() {
+    let root = parse_syntax(FileId::default(), SOURCE).unwrap();
+
+    assert_eq!(root.kind(), SyntaxKind::SourceFile);
+    assert_eq!(root.text(), SOURCE);
+    assert_eq!(root.span().len(), SOURCE.len());
+
+    let kinds: Vec<SyntaxKind> = root.children().iter().map(SyntaxNode::kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            SyntaxKind::FunctionDeclaration,
+            SyntaxKind::StructDeclaration
+        ]
+    );
+
+    let text: String = root.tokens().iter().map(SyntaxToken::text).collect();
+    assert_eq!(text, SOURCE);
+}
+
+#[test]
+fn nodes_keep_punctuation_and_comments() {
+    let root = parse_syntax(FileId::default(), SOURCE).unwrap();
+
+    let function = find(&root, SyntaxKind::FunctionDeclaration);
+    assert!(function.text().starts_with("fn blink("));
+    assert!(function.text().contains("# Count down."));
+
+    let ret = find(&root, SyntaxKind::Return);
+    assert_eq!(ret.text(), "return ( times + 1 ) * 2;");
+    assert_eq!(ret.children()[0].text(), "( times + 1 ) * 2");
+    assert_eq!(
+        ret.parent().unwrap().kind(),
+        SyntaxKind::FunctionDeclaration
+    );
+
+    let comma = root
+        .tokens()
+        .into_iter()
+        .rfind(|token| token.kind() == TokenKind::Comma)
+        .unwrap();
+    assert_eq!(&SOURCE[comma.span().start as usize..], ", }\n");
+}
+
+#[test]
+fn typed_views_match_the_ast() {
+    let root = parse_syntax(FileId::default(), SOURCE).unwrap();
+    let statements = parse(
+        FileId::default(),
+        tokenize(FileId::default(), SOURCE).unwrap(),
+    )
+    .unwrap();
+
+    let function: ast::FunctionDeclaration =
+        find(&root, SyntaxKind::FunctionDeclaration).cast().unwrap();
+    match &statements[0] {
+        Statement::FunctionDeclaration(expected) => {
+            assert_eq!(function.identifier().unwrap(), "blink");
+            assert_eq!(function.span(), expected.span);
+            assert_eq!(function.parameters()[1].span(), expected.parameters[1].span);
+        }
+        statement => panic!("unexpected statement {:?}", statement),
+    }
+    assert_eq!(function.return_type().unwrap().name().unwrap(), "u8");
+    assert!(function.parameters()[0].field_type().unwrap().is_pointer());
+
+    let body = function.body().unwrap();
+    assert!(matches!(body[0], ast::BodyStatement::While(_)));
+    match &body[1] {
+        ast::BodyStatement::Return(ret) => {
+            let value = ret.value().unwrap();
+            assert_eq!(value.binary_operator(), Some(BinaryOperator::Multiply));
+            assert_eq!(
+                value.operands()[0].binary_operator(),
+                Some(BinaryOperator::Add)
+            );
+        }
+        statement => panic!("unexpected statement {:?}", statement),
+    }
+
+    let point: ast::StructUnionDeclaration = root.children()[1].cast().unwrap();
+    assert_eq!(point.fields().unwrap().len(), 2);
+
+    let condition: ast::Expression = find(&root, SyntaxKind::While).children()[0].cast().unwrap();
+    assert_eq!(condition.to_string(), "(times > 0)");
+    assert_eq!(
+        condition.binary_operator(),
+        Some(BinaryOperator::GreaterThan)
+    );
+
+    let assignment: ast::AssignmentStatement = find(&root, SyntaxKind::Assignment).cast().unwrap();
+    let value = assignment.value().unwrap();
+    assert_eq!(assignment.operator(), Some(AssignmentOperator::Assign));
+    assert_eq!(value.unary_operator(), Some(UnaryOperator::Not));
+    assert_eq!(value.binary_operator(), None);
+
+    assert!(root.children()[0]
+        .cast::<ast::StructUnionDeclaration>()
+        .is_none());
+}
+
+#[test]
+fn member_views() {
+    let source = "\
+enum Color { Red, Green = 2, }
+fn f(c: Color): u8 {
+    switch (c) {
+        case 2: return 1;
+        default: return (Point { x: 1 }).x;
+    }
+}
+";
+    let root = parse_syntax(FileId::default(), source).unwrap();
+
+    let variants: Vec<ast::EnumVariant> = root.children()[0]
+        .children()
+        .iter()
+        .filter_map(SyntaxNode::cast)
+        .collect();
+    assert_eq!(variants[1].identifier().unwrap(), "Green");
+    assert_eq!(variants[1].value().unwrap().to_string(), "2");
+
+    let field: ast::Field = find(
+        &parse_syntax(FileId::default(), SOURCE).unwrap(),
+        SyntaxKind::Field,
+    )
+    .cast()
+    .unwrap();
+    assert_eq!(field.identifier().unwrap(), "led");
+
+    let switch: ast::SwitchStatement = find(&root, SyntaxKind::Switch).cast().unwrap();
+    let cases = switch.cases();
+    assert_eq!(cases[0].value().unwrap().to_string(), "2");
+    assert_eq!(cases[0].body().len(), 1);
+    assert!(cases[1].value().is_none());
+
+    let initializer: ast::FieldInitializer =
+        find(&root, SyntaxKind::FieldInitializer).cast().unwrap();
+    assert_eq!(initializer.identifier().unwrap(), "x");
+    assert_eq!(initializer.value().unwrap().to_string(), "1");
+}
+
+#[test]
+fn views_of_broken_nodes() {
+    let root = parse_syntax(FileId::default(), "const A: u8 = 1;").unwrap();
+
+    let literal = find(&root, SyntaxKind::Expression).tokens()[0].clone();
+    let edited = literal.replace_with(")");
+
+    let constant: ast::ConstantVariableDeclaration = edited.children()[0].cast().unwrap();
+    assert_eq!(constant.identifier().unwrap(), "A");
+    assert!(constant.value().unwrap().unary_operator().is_none());
+}
+
+#[test]
+fn editing_keeps_formatting() {
+    let root = parse_syntax(FileId::default(), SOURCE).unwrap();
+
+    let name = find(&root, SyntaxKind::FunctionDeclaration)
+        .tokens()
+        .into_iter()
+        .find(|token| token.kind() == TokenKind::Identifier("blink"))
+        .unwrap();
+
+    let edited = name.replace_with("toggle");
+    assert_eq!(edited.text(), SOURCE.replacen("blink", "toggle", 1));
+    assert_eq!(root.text(), SOURCE);
+
+    let function: ast::FunctionDeclaration = edited.children()[0].cast().unwrap();
+    assert_eq!(function.identifier().unwrap(), "toggle");
+
+    // Untouched subtrees are shared between the two trees.
+    assert!(std::rc::Rc::ptr_eq(
+        root.children()[1].green(),
+        edited.children()[1].green()
+    ));
+}
+
+#[test]
+fn syntax_tree_with_errors_is_lossless() {
+    let source = "const A: u8 = ;\nvar b: u8 = 1 + 2;\n";
+    let (errors, root) = parse_syntax(FileId::default(), source).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(root.text(), source);
+
+    let kinds: Vec<SyntaxKind> = root.children().iter().map(SyntaxNode::kind).collect();
+    assert_eq!(kinds, [SyntaxKind::Error, SyntaxKind::VariableDeclaration]);
+}
+
+#[test]
+fn lexical_errors_are_reported_once() {
+    for source in ["const A: u8 = 70000;", "const A: u8 = 1 + $;"] {
+        let (errors, root) = parse_syntax(FileId::default(), source).unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{}: {:?}", source, errors);
+        assert!(matches!(errors[0], CompilerError::LexError(_)));
+        assert_eq!(root.children()[0].kind(), SyntaxKind::ConstantDeclaration);
+    }
+}